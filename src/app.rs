@@ -1,26 +1,77 @@
+use crate::clock::Clock;
+use crate::command::{self, LineCommand, Verb};
+use crate::config::{
+    self, Config, DeleteSelection, EmptyTitle, Feedback, JsonFormat, ModalSize, MultilinePaste,
+    QueryStatus, SavedQuery, SearchCommit, Separator, SortKey, TagOrder, Theme, WrapMode,
+};
 use crate::data;
-use crate::widget::{InputField, Wrap};
+use crate::logging;
+use crate::markdown;
+use crate::widget::{self, InputField, Motion, Wrap};
+use chrono::{DateTime, Local, NaiveDate};
 use color_eyre::eyre::Result;
-use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent};
+use ratatui::crossterm::event::{
+    self, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyModifiers,
+};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{self, EnterAlternateScreen};
 use ratatui::layout::{Constraint, Flex, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::symbols::border;
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::widgets::{
+    Block, BorderType, Borders, Clear, LineGauge, List, ListItem, ListState, Paragraph,
+};
 use ratatui::{DefaultTerminal, Frame};
 use serde::{Deserialize, Serialize};
-use tui_input::Input;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
 use tui_input::backend::crossterm::EventHandler;
+use tui_input::{Input, InputRequest};
 
-const SELECTED_STYLE: Style = Style::new()
-    .bg(Color::DarkGray)
-    .add_modifier(Modifier::BOLD);
 const KEYBIND_STYLE: Style = Style::new().bold().fg(Color::Yellow);
-const COMPLETED_STYLE: Style = Style::new().fg(Color::Green);
+const TAG_STYLE: Style = Style::new().fg(Color::Cyan);
+const PREVIEW_STYLE: Style = Style::new().fg(Color::DarkGray);
+const DUE_STYLE: Style = Style::new().fg(Color::Magenta);
+const OVERDUE_STYLE: Style = Style::new().fg(Color::Red);
+const ERROR_STYLE: Style = Style::new().fg(Color::Red).bold();
+const MARKED_STYLE: Style = Style::new()
+    .fg(Color::Magenta)
+    .add_modifier(Modifier::ITALIC);
+const ASCII_BORDER: border::Set = border::Set {
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    vertical_left: "|",
+    vertical_right: "|",
+    horizontal_top: "-",
+    horizontal_bottom: "-",
+};
+const DEMOTE_PROMPT: &str = "Pick a parent: [Enter] to confirm, [Esc] to cancel";
+const TICK_RATE: Duration = Duration::from_millis(250);
+const UNDO_LIMIT: usize = 50;
+const CLIPBOARD_TOOLS: [&str; 4] = [
+    "pbcopy",
+    "wl-copy",
+    "xclip -selection clipboard",
+    "xsel --clipboard --input",
+];
+const CHORD_TIMEOUT: Duration = Duration::from_secs(1);
 
 /// Holds current application state
 #[derive(Default)]
 pub struct App {
+    config: Config,
     todo_list: TodoList,
+    /// The name of the list being shown
+    list_name: String,
+    /// The other lists, kept aside until switched to
+    lists: BTreeMap<String, Vec<TodoItem>>,
     editing_index: Option<usize>,
     view: View,
     input: Input,
@@ -28,14 +79,70 @@ pub struct App {
     edit_mode: Option<EditMode>,
     exit: bool,
     yank_buffer: Option<TodoItem>,
+    last_deleted: Option<(usize, TodoItem)>,
+    undo_stack: Vec<Undo>,
+    last_action: Option<Action>,
+    pending_key: Option<char>,
+    field_register: Option<String>,
+    data_path: PathBuf,
+    message: Option<String>,
+    last_activity: Option<Instant>,
+    external: Option<External>,
+    search: Input,
+    searching: bool,
+    command_line: Option<Input>,
+    disk_stamp: Option<data::FileStamp>,
+    focus_memory: HashMap<u64, Focus>,
+    demoting: Option<usize>,
+    confirming_completion: Option<usize>,
+    /// The id of the item waiting for its deletion to be confirmed
+    confirming_delete: Option<u64>,
+    pending_target: Option<(usize, Instant)>,
+    notified: HashSet<u64>,
+    marked: HashSet<u64>,
+    editor_area: Rect,
+    unyanked: Option<String>,
+    reload_prompt: bool,
+    dismissed_stamp: Option<data::FileStamp>,
+    feedback: bool,
+    last_change: Option<Instant>,
+    sort: Sort,
+    status_filter: StatusFilter,
+    tag_filter: Option<String>,
+    fallback_path: Option<PathBuf>,
+    exit_unsaved: bool,
+    clock: Box<dyn Clock>,
+    seed_examples: bool,
+    file: Option<PathBuf>,
+    /// The last failure to load or save, shown until either succeeds
+    error: Option<String>,
+    /// The id of the item asking for the time it took, and the minutes typed so far
+    time_prompt: Option<(u64, Input)>,
+    palette: Option<Palette>,
 }
 
 /// Represents a task to be done
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct TodoItem {
-    status: Status,
-    todo: String,
-    info: String,
+    #[serde(default)]
+    pub id: u64,
+    pub status: Status,
+    pub todo: String,
+    pub info: String,
+    #[serde(default)]
+    pub subtasks: Vec<TodoItem>,
+    #[serde(default)]
+    pub completed_at: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub modified_at: Option<DateTime<Local>>,
+    #[serde(default)]
+    pub due: Option<NaiveDate>,
+    #[serde(default)]
+    pub actual_minutes: Option<u32>,
+    #[serde(default)]
+    pub priority: Priority,
 }
 
 /// Wrapper around a Vec of TodoItems and the ListState (for the List Widget)
@@ -45,20 +152,32 @@ pub struct TodoList {
     state: ListState,
 }
 
-/// Represents whether a TodoItem is done or not
-#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+/// Represents whether a TodoItem is done, being worked on or not started
+#[derive(Clone, Serialize, Deserialize, Debug, Default, PartialEq)]
 pub enum Status {
     #[default]
     Todo,
+    InProgress,
     Completed,
 }
 
+/// Represents how important a TodoItem is
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
 /// Represents a "page" of the app
 #[derive(Default)]
 pub enum View {
     #[default]
     List,
     Edit,
+    Detail,
+    Scratch,
 }
 
 /// Represents a vim-like editor mode
@@ -68,280 +187,2137 @@ pub enum EditMode {
     Insert,
 }
 
+/// A List View action that can be run by name from the command palette
+type PaletteCommand = (&'static str, fn(&mut App));
+
+/// Every action of the command palette, in the order they are listed
+const PALETTE_COMMANDS: &[PaletteCommand] = &[
+    ("Add item", App::add_entry),
+    ("Insert item below", |app| app.insert_entry_relative(true)),
+    ("Insert item above", |app| app.insert_entry_relative(false)),
+    ("Edit item", App::edit_entry),
+    ("View item details", App::view_entry),
+    ("Toggle status", |app| app.perform(Action::ToggleStatus)),
+    ("Cycle status", |app| app.perform(Action::CycleStatus)),
+    ("Delete item", |app| app.perform(Action::Delete)),
+    ("Restore deleted item", App::restore_deleted),
+    ("Undo", App::undo),
+    ("Yank item", App::yank_entry),
+    ("Paste item", App::paste_entry),
+    ("Merge item", App::merge_entry),
+    ("Demote item", App::start_demote),
+    ("Promote subtask", App::promote_entry),
+    ("Search", App::start_search),
+    ("Cycle sort", App::cycle_sort),
+    ("Sort by priority", App::sort_by_priority),
+    ("Cycle priority", |app| app.perform(Action::CyclePriority)),
+    ("Show incomplete items", |app| {
+        app.filter_status(StatusFilter::Todo)
+    }),
+    ("Show completed items", |app| {
+        app.filter_status(StatusFilter::Completed)
+    }),
+    ("Show all items", |app| app.filter_status(StatusFilter::All)),
+    ("Cycle tag filter", App::cycle_tag_filter),
+    ("Next list", |app| app.cycle_list(true)),
+    ("Previous list", |app| app.cycle_list(false)),
+    ("Reset view", App::reset_view),
+    ("Move item up", App::move_entry_up),
+    ("Move item down", App::move_entry_down),
+    ("Mark item", App::toggle_mark),
+    ("Mark all items", App::mark_all),
+    ("Unmark all items", |app| app.marked.clear()),
+    ("Copy list as markdown", App::yank_list),
+    ("Toggle pretty JSON", App::toggle_json_format),
+    ("Open info in pager", |app| {
+        app.external = Some(External::Pager)
+    }),
+    ("Edit item as JSON", |app| {
+        app.external = Some(External::EditJson)
+    }),
+    ("Open scratchpad", |app| app.switch_view(View::Scratch)),
+    ("Command line", |app| {
+        app.command_line = Some(Input::default())
+    }),
+    ("Quit", App::exit),
+];
+
+/// The open command palette, its query and the selected position among the matches
+#[derive(Default)]
+struct Palette {
+    query: Input,
+    selected: usize,
+}
+
+/// Represents a task that has to run outside of the TUI
+#[derive(Clone, Copy)]
+pub enum External {
+    EditJson,
+    Pager,
+}
+
+/// Represents the order items are displayed in List View (outside of searches)
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Sort {
+    #[default]
+    Manual,
+    Modified,
+    /// By the sort keys in the config
+    Keys,
+    /// Highest priority first, completed items last
+    Priority,
+}
+
+/// Represents which statuses are shown in List View
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum StatusFilter {
+    #[default]
+    All,
+    Todo,
+    Completed,
+}
+
+/// Represents how to reverse a List View change, items are found by their id
+#[derive(Debug)]
+enum Undo {
+    /// Reinserts a deleted item where it was
+    Delete(usize, TodoItem),
+    /// Restores an item to how it was before it changed
+    Change(TodoItem),
+    /// Removes an added item
+    Add(u64),
+    /// Swaps two moved items back
+    Move(u64, u64),
+    /// Restores a merged item to how it was and reinserts the item merged into it
+    Merge(TodoItem, usize, TodoItem),
+}
+
+/// Represents a mutating List View action that can be repeated
+#[derive(Clone, Copy)]
+pub enum Action {
+    ToggleStatus,
+    CycleStatus,
+    Delete,
+    CyclePriority,
+}
+
 /// Represents the currently selected input field
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Focus {
     Todo,
+    Tags,
+    Due,
     Info,
 }
 
 impl App {
     /// Handles main application loop
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        // Read config from file, a broken config falls back to the defaults
+        let config_error = match config::read_config() {
+            Ok(config) => {
+                self.config = config.unwrap_or_default();
+                None
+            }
+            Err(err) => Some(format!("Failed to read config: {err}")),
+        };
+        // Use the preset from the environment unless the config picks one
+        if self.config.theme.preset.is_none() {
+            self.config.theme.preset = config::preset_from_env();
+        }
+        // Fall back to ASCII on terminals that likely lack Unicode
+        let ascii = &mut self.config.theme.ascii;
+        ascii.get_or_insert_with(config::detect_ascii);
+        // Resolve which file holds the todos, a file given on start up wins
+        if let Ok(path) = data::resolve_path(&self.config, self.file.as_deref()) {
+            self.data_path = path;
+        }
+        _ = logging::init_from_env(&self.data_path.with_file_name(logging::LOG_FILE));
+        self.check_writable();
+        if self.file.is_none()
+            && let Ok(names) = data::colliding_profiles(&self.config)
+            && !names.is_empty()
+        {
+            let names = names.join(", ");
+            self.message = Some(format!("Warning: todos file is shared with {names}"));
+        }
         // Read todos from file
-        if let Ok(list) = data::read_todos() {
-            self.todo_list.items = list;
+        self.load();
+        // Loading clears the error, so the config error is shown after it
+        if self.error.is_none() {
+            self.error = config_error;
         }
         while !self.exit {
             // Rendering
             terminal.draw(|frame| self.render(frame))?;
+            if self.feedback {
+                self.feedback = false;
+                if self.config.feedback == Feedback::Bell {
+                    io::stdout().write_all(b"\x07")?;
+                    io::stdout().flush()?;
+                }
+            }
             // Input handling
             self.handle_events()?;
+            // External programs
+            if let Some(external) = self.external.take() {
+                self.run_external(terminal, external)?;
+            }
         }
-        return Ok(());
+        Ok(())
     }
 
-    /// Handles all input events from user (discards non-key events)
-    fn handle_events(&mut self) -> Result<()> {
-        if let Some(key) = event::read()?.as_key_press_event() {
-            self.handle_key_event(key);
-        }
-        return Ok(());
+    /// Adds example todos when there is no todos file yet
+    pub fn seed_examples(&mut self) {
+        self.seed_examples = true;
     }
 
-    /// Handles keyboard inputs from user
-    fn handle_key_event(&mut self, key: KeyEvent) {
-        match self.view {
-            View::List => self.handle_list_key_event(key),
-            View::Edit => self.handle_edit_key_event(key),
+    /// Keeps the todos in this file instead of the configured one
+    pub fn use_file(&mut self, path: PathBuf) {
+        self.file = Some(path);
+    }
+
+    /// Returns the list as plain text to print after the TUI closes, if configured
+    /// Followed by the list yanked as markdown, if there was no clipboard to copy it to
+    pub fn exit_summary(&self) -> Option<String> {
+        let summary = match self.config.print_on_exit {
+            true => Some(plaintext_summary(&self.todo_list.items)),
+            false => None,
+        };
+        match (summary, &self.unyanked) {
+            (Some(summary), Some(markdown)) => Some(format!("{summary}\n{markdown}")),
+            (summary, markdown) => summary.or_else(|| markdown.clone()),
         }
     }
 
-    /// Responsible for handling keyboard input in List View
-    fn handle_list_key_event(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Char('q') => self.exit(),
-            KeyCode::Char('j') => self.todo_list.state.select_next(),
-            KeyCode::Char('k') => self.todo_list.state.select_previous(),
-            KeyCode::Char('g') => self.todo_list.state.select_first(),
-            KeyCode::Char('G') => self.todo_list.state.select_last(),
-            KeyCode::Char('c') => self.toggle_status(),
-            KeyCode::Char('d') => self.delete_entry(),
-            KeyCode::Char('a') => self.add_entry(),
-            KeyCode::Char('i') => self.edit_entry(),
-            KeyCode::Char('y') => self.yank_entry(),
-            KeyCode::Char('p') => self.paste_entry(),
-            _ => {}
+    /// Handles all input events from user (discards non-key events)
+    /// Waits at most one tick for an event, then runs the tick logic
+    fn handle_events(&mut self) -> Result<()> {
+        if event::poll(TICK_RATE)? {
+            match event::read()? {
+                Event::Paste(text) => {
+                    self.last_activity = Some(Instant::now());
+                    self.handle_paste(&text);
+                }
+                event => {
+                    if let Some(key) = event.as_key_press_event() {
+                        self.last_activity = Some(Instant::now());
+                        logging::debug(format!("Key {:?} {:?}", key.code, key.modifiers));
+                        self.handle_key_event(key);
+                    }
+                }
+            }
         }
+        self.on_tick(Instant::now());
+        Ok(())
     }
 
-    /// Responsible for handling keyboard input in Edit View
-    fn handle_edit_key_event(&mut self, key: KeyEvent) {
-        let edit_mode = self.edit_mode.as_ref().expect("Expected an editor mode.");
-        match edit_mode {
-            EditMode::Normal => match key.code {
-                KeyCode::Char('q') => self.switch_view(View::List),
-                KeyCode::Char('i') => self.edit_mode = Some(EditMode::Insert),
-                KeyCode::Char('j') => self.focus_down(),
-                KeyCode::Char('k') => self.focus_up(),
-                _ => {}
-            },
-            EditMode::Insert => match key.code {
-                KeyCode::Esc => self.edit_mode = Some(EditMode::Normal),
-                _ => {
-                    self.input.handle_event(&Event::Key(key));
+    /// Suspends the TUI to run an external task
+    fn run_external(&mut self, terminal: &mut DefaultTerminal, external: External) -> Result<()> {
+        match external {
+            External::EditJson => {
+                let index = match self.selected_index() {
+                    Some(val) => val,
+                    None => return Ok(()),
+                };
+                let item = &self.todo_list.items[index];
+                let result = suspend(terminal, || data::edit_as_json(item, open_editor))?;
+                match result {
+                    Ok(item) => {
+                        self.todo_list.items[index] = item;
+                        self.mark_changed();
+                    }
+                    Err(data::Error::Serde(_)) => {
+                        self.message = Some(String::from("Invalid JSON, item left unchanged"));
+                    }
+                    Err(_) => {
+                        self.message = Some(String::from("Failed to run $EDITOR"));
+                    }
                 }
-            },
+            }
+            External::Pager => {
+                let Some(index) = self.selected_index() else {
+                    return Ok(());
+                };
+                let info = self.todo_list.items[index].info.clone();
+                let result = suspend(terminal, || open_pager(&info))?;
+                self.finish_paging(result);
+            }
         }
+        Ok(())
     }
 
-    /// Marks the app for closure
-    fn exit(&mut self) {
-        self.exit = true;
-        _ = data::write_todos(&self.todo_list.items);
+    /// Falls back to the Detail View when the pager couldn't show the info
+    fn finish_paging(&mut self, result: io::Result<()>) {
+        if let Err(err) = result {
+            logging::error(format!("Failed to run the pager: {err}"));
+            self.message = Some(String::from(
+                "Failed to run $PAGER, showing details instead",
+            ));
+            self.switch_view(View::Detail);
+        }
     }
 
-    /// Toggles a TodoItem from Todo to Complete or vice-versa
-    fn toggle_status(&mut self) {
-        if let Some(i) = self.todo_list.state.selected() {
-            self.todo_list.items[i].status = match self.todo_list.items[i].status {
-                Status::Todo => Status::Completed,
-                Status::Completed => Status::Todo,
+    /// Handles time based behavior, runs at least once per tick
+    fn on_tick(&mut self, now: Instant) {
+        if let Some((number, started)) = self.pending_target
+            && now.duration_since(started) >= CHORD_TIMEOUT
+        {
+            self.pending_target = None;
+            // A lone digit that no action followed does what it does without chords
+            match number {
+                1 => self.filter_status(StatusFilter::Todo),
+                2 => self.filter_status(StatusFilter::Completed),
+                0 => self.filter_status(StatusFilter::All),
+                _ => {}
             }
         }
+        // A dismissed prompt stays hidden until the file or the todos change again
+        let stamp = data::stamp(&self.data_path);
+        let dismissed = stamp.is_some() && stamp == self.dismissed_stamp;
+        if data::changed_externally(self.disk_stamp, stamp) && !dismissed {
+            self.reload_prompt = true;
+        }
+        self.notify_due_soon(self.clock.now().date_naive());
+        let idle = self.config.auto_save_secs.map(Duration::from_secs);
+        if should_auto_save(self.last_activity, now, idle) {
+            self.auto_save();
+        }
+        let debounce = Duration::from_millis(self.config.save_debounce_ms);
+        if self.config.save_on_change && should_flush(self.last_change, now, debounce) {
+            self.flush();
+        }
     }
 
-    /// Deletes the currently selected TodoItem
-    fn delete_entry(&mut self) {
-        if let Some(index) = self.todo_list.state.selected() {
-            self.yank_entry();
-            self.todo_list.items.remove(index);
+    /// Reads the todos from file, remembering the state of the file
+    fn load(&mut self) {
+        // Only a first run (without a todos file) gets the examples
+        if self.seed_examples || self.config.starter_examples {
+            let today = self.clock.now().date_naive();
+            match data::seed_examples(&self.data_path, self.config.json_format, today) {
+                Ok(true) => self.message = Some(String::from("Added examples, tagged #example")),
+                Ok(false) => {}
+                Err(err) => logging::error(format!("Failed to add examples: {err}")),
+            }
+        }
+        self.disk_stamp = data::stamp(&self.data_path);
+        let file = match data::read_todos(&self.data_path) {
+            Ok(file) => Some(file),
+            // Every list starts without a file
+            Err(data::Error::IO(err)) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => {
+                self.error = Some(format!("Failed to load: {err}"));
+                None
+            }
+        };
+        if let Some(mut file) = file {
+            self.error = None;
+            let fixed = data::dedup_ids(&mut file);
+            self.todo_list.items = file.take_items();
+            self.list_name = file.list;
+            self.lists = file.lists;
+            if fixed > 0 {
+                logging::info(format!("Fixed {fixed} duplicate ids"));
+                self.message = Some(format!("Fixed {fixed} duplicate ids"));
+            }
+            self.apply_rollups();
+            // Pick up where the last session left off, unless the item is gone
+            self.todo_list.state.select(None);
+            if let Some(index) = file.selected.and_then(|id| self.position_of(id)) {
+                self.select_item(index);
+            }
+        }
+        if self.list_name.is_empty() {
+            self.list_name = String::from(data::DEFAULT_LIST);
+        }
+        // Select the first item so actions work right away
+        if self.todo_list.state.selected().is_none() && !self.visible().is_empty() {
+            self.todo_list.state.select_first();
         }
     }
 
-    /// Adds a new TodoItem to the list and enters Edit View
-    fn add_entry(&mut self) {
-        self.todo_list
-            .items
-            .push(TodoItem::new(Status::Todo, "", ""));
-        self.input.reset();
-        self.todo_list.state.select_last();
-        self.editing_index = Some(self.todo_list.items.len() - 1);
-        self.switch_view(View::Edit);
-        self.edit_mode = Some(EditMode::Insert);
+    /// Warns when the todos can't be saved, offering to save them elsewhere
+    fn check_writable(&mut self) {
+        if let Err(err) = data::check_writable(&self.data_path) {
+            logging::error(format!(
+                "Can't write to {}: {err}",
+                self.data_path.display()
+            ));
+            let fallback = data::fallback_path(&self.data_path);
+            self.message = Some(format!(
+                "Warning: can't save here, press [W] to save to {}",
+                fallback.display()
+            ));
+            self.fallback_path = Some(fallback);
+        }
     }
 
-    /// Edits an existing TodoItem
-    fn edit_entry(&mut self) {
-        let err = "Expected a selected ListItem in Edit View.";
-        let index = self.todo_list.state.selected().expect(err);
-        let selected_item = self.todo_list.items.get(index).expect(err);
+    /// Saves the todos to the fallback file from now on
+    fn use_fallback_path(&mut self) {
+        let Some(path) = self.fallback_path.take() else {
+            return self.noop();
+        };
+        self.data_path = path;
+        self.disk_stamp = data::stamp(&self.data_path);
+        if self.save() {
+            self.message = Some(format!("Saving to {}", self.data_path.display()));
+        }
+    }
 
-        self.input = Input::new(selected_item.todo.clone());
-        self.editing_index = Some(index);
-        self.switch_view(View::Edit);
-        self.edit_mode = Some(EditMode::Normal);
+    /// Writes the todos to file, unless that would overwrite changes made outside of the app
+    /// Returns whether the todos were written
+    fn save(&mut self) -> bool {
+        if data::changed_externally(self.disk_stamp, data::stamp(&self.data_path)) {
+            logging::info("Not saving, the todos file changed on disk");
+            self.reload_prompt = true;
+            return false;
+        }
+        self.overwrite()
     }
 
-    /// Yanks (copies) an entry if an item is selected, None otherwise
-    fn yank_entry(&mut self) {
-        let index = self.todo_list.state.selected();
-        let entry = if let Some(index) = index {
-            self.todo_list.items.get(index).cloned()
-        } else {
-            None
+    /// Writes the todos to file, even if it changed outside of the app
+    /// Returns whether the todos were written, showing the error if they weren't
+    fn overwrite(&mut self) -> bool {
+        self.reload_prompt = false;
+        let mut file = data::TodoFile {
+            selected: self.selected_index().map(|i| self.todo_list.items[i].id),
+            list: self.list_name.clone(),
+            lists: self.lists.clone(),
         };
-        self.yank_buffer = entry;
+        file.lists
+            .insert(self.list_name.clone(), self.todo_list.items.clone());
+        let result = data::write_todos(&file, &self.data_path, self.config.json_format);
+        if let Err(err) = result {
+            self.message = Some(format!("Failed to save: {err}"));
+            self.error = self.message.clone();
+            return false;
+        }
+        self.error = None;
+        self.exit_unsaved = false;
+        self.disk_stamp = data::stamp(&self.data_path);
+        true
     }
 
-    /// Pastes an entry below the selection, or index 0 if nothing is selected
-    fn paste_entry(&mut self) {
-        let entry = match &self.yank_buffer {
-            Some(val) => val,
-            None => return,
-        };
+    /// Replaces the todos with the ones on file, discarding unsaved changes
+    fn reload(&mut self) {
+        self.reload_prompt = false;
+        self.last_change = None;
+        self.last_deleted = None;
+        self.undo_stack.clear();
+        // Prompts about items may point at different items after reloading
+        self.confirming_delete = None;
+        self.confirming_completion = None;
+        self.time_prompt = None;
+        self.demoting = None;
+        self.palette = None;
+        if matches!(self.view, View::Edit | View::Detail) {
+            self.view = View::List;
+            self.edit_mode = None;
+            self.focus = None;
+        }
+        self.todo_list.items.clear();
+        self.lists.clear();
+        self.load();
+    }
 
-        let index = match self.todo_list.state.selected() {
-            None => 0,
-            Some(val) => val + 1,
+    /// Shows a toast for the incomplete items that became due soon, once per item per session
+    fn notify_due_soon(&mut self, today: NaiveDate) {
+        let Some(window) = self.config.due_soon_days else {
+            return;
         };
+        let mut due_soon = Vec::new();
+        for item in &self.todo_list.items {
+            let notified = self.notified.contains(&item.id);
+            if !matches!(item.status, Status::Completed)
+                && let Some(due) = item.due
+                && should_notify(due, today, window, notified)
+            {
+                self.notified.insert(item.id);
+                due_soon.push(item.todo.as_str());
+            }
+        }
+        if !due_soon.is_empty() {
+            self.message = Some(format!("Due soon: {}", due_soon.join(", ")));
+        }
+    }
 
-        self.todo_list.items.insert(index, entry.clone());
+    /// Records a change to the todos, to be saved once changes settle (if configured)
+    fn mark_changed(&mut self) {
+        self.last_change = Some(Instant::now());
+        self.dismissed_stamp = None;
     }
 
-    /// Sets the application view
-    fn switch_view(&mut self, view: View) {
-        // Do any necessary cleanup
+    /// Writes pending changes to file
+    fn flush(&mut self) {
+        self.last_change = None;
+        self.save();
+    }
+
+    /// Commits the input, leaves Insert mode and writes the todos to file
+    fn auto_save(&mut self) {
+        self.last_activity = None;
         match self.view {
-            View::Edit => self.save_input(),
+            View::Edit => {
+                self.save_input();
+                self.edit_mode = Some(EditMode::Normal);
+            }
+            View::Scratch => {
+                self.save_scratch();
+                self.edit_mode = Some(EditMode::Normal);
+            }
             _ => {}
         }
-        // Do any necessary setup
-        match view {
-            View::List => {
-                self.edit_mode = None;
-                self.focus = None;
+        self.save();
+    }
+
+    /// Handles keyboard inputs from user
+    fn handle_key_event(&mut self, key: KeyEvent) {
+        self.message = None;
+        if self.reload_prompt {
+            return self.handle_reload_key_event(key);
+        }
+        match self.view {
+            View::List => self.handle_list_key_event(key),
+            View::Edit => self.handle_edit_key_event(key),
+            View::Detail => self.handle_detail_key_event(key),
+            View::Scratch => self.handle_scratch_key_event(key),
+        }
+    }
+
+    /// Handles text pasted all at once (bracketed paste), only while typing in Insert mode
+    fn handle_paste(&mut self, text: &str) {
+        self.message = None;
+        if !matches!(self.edit_mode, Some(EditMode::Insert)) {
+            return;
+        }
+        let text = widget::sanitize(text);
+        match (&self.view, &self.focus) {
+            (View::Edit, Some(Focus::Todo)) if text.trim().contains('\n') => {
+                self.paste_lines(&text);
             }
-            View::Edit => {
-                self.focus = Some(Focus::Todo);
+            (View::Edit, Some(Focus::Todo | Focus::Tags | Focus::Due)) => {
+                self.insert_text(&text.replace('\n', " "));
             }
+            _ => self.insert_text(&text),
         }
-        self.view = view;
     }
 
-    /// Switches to desired 'Focus' (input field)
-    fn switch_focus(&mut self, focus: Focus) {
+    /// Pastes several lines into the Todo field, the rest of the lines go where configured
+    fn paste_lines(&mut self, text: &str) {
+        let err = "Expected an item being edited while pasting.";
+        let index = self.editing_index.expect(err);
+        let (title, rest) = split_paste(text);
+        self.insert_text(&title);
         self.save_input();
+        match self.config.multiline_paste {
+            MultilinePaste::Info => {
+                let now = self.clock.now();
+                let item = &mut self.todo_list.items[index];
+                let info = match item.info.is_empty() {
+                    true => rest,
+                    false => format!("{}\n{rest}", item.info),
+                };
+                item.set_info(info, now);
+            }
+            MultilinePaste::Items => {
+                for (offset, line) in rest.lines().enumerate() {
+                    if self.refuse_when_full() {
+                        break;
+                    }
+                    let mut item = TodoItem::new(Status::Todo, line, "");
+                    item.id = self.next_id();
+                    self.todo_list.items.insert(index + 1 + offset, item);
+                }
+            }
+        }
+        self.mark_changed();
+    }
 
-        let err = "Expected a selected ListItem in Edit View.";
-        let index = self.editing_index.expect(err);
-        let selected_item = self.todo_list.items.get(index).expect(err);
+    /// Types text into the Input at the cursor
+    fn insert_text(&mut self, text: &str) {
+        for c in text.chars() {
+            self.input.handle(InputRequest::InsertChar(c));
+        }
+    }
 
-        match focus {
-            Focus::Todo => {
-                self.input = Input::new(selected_item.todo.clone());
+    /// Responsible for handling keyboard input while asking to reload the file
+    fn handle_reload_key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('r') => self.reload(),
+            KeyCode::Char('o') => {
+                self.overwrite();
             }
-            Focus::Info => {
-                self.input = Input::new(selected_item.info.clone());
+            // Keeps the todos as they are, without asking again until something changes
+            KeyCode::Esc => {
+                self.reload_prompt = false;
+                self.dismissed_stamp = data::stamp(&self.data_path);
             }
+            _ => self.noop(),
         }
-        self.focus = Some(focus);
     }
 
-    /// Graphically switches to the Focus below the current one
-    fn focus_down(&mut self) {
-        if let Some(focus) = &self.focus {
-            let below = match focus {
-                Focus::Todo => Focus::Info,
-                Focus::Info => Focus::Info,
-            };
-            self.switch_focus(below);
+    /// Responsible for handling keyboard input in List View
+    fn handle_list_key_event(&mut self, key: KeyEvent) {
+        if self.searching {
+            return self.handle_search_key_event(key);
+        }
+        if self.command_line.is_some() {
+            return self.handle_command_key_event(key);
+        }
+        if self.demoting.is_some() {
+            return self.handle_demote_key_event(key);
+        }
+        if self.confirming_completion.is_some() {
+            return self.handle_confirm_completion_key_event(key);
+        }
+        if self.confirming_delete.is_some() {
+            return self.handle_confirm_delete_key_event(key);
+        }
+        if self.time_prompt.is_some() {
+            return self.handle_time_prompt_key_event(key);
+        }
+        if self.palette.is_some() {
+            return self.handle_palette_key_event(key);
+        }
+        if self.config.numeric_chords && self.handle_chord_key_event(key) {
+            return;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('z') if ctrl => self.restore_deleted(),
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('a') if ctrl => self.mark_all(),
+            KeyCode::Char('p') if ctrl => self.palette = Some(Palette::default()),
+            KeyCode::Char('d') if ctrl => self.marked.clear(),
+            KeyCode::Char('q') => self.exit(),
+            KeyCode::Char('j') => self.select_next(),
+            KeyCode::Char('J') => self.move_entry_down(),
+            KeyCode::Char('K') => self.move_entry_up(),
+            KeyCode::Char('k') => self.select_previous(),
+            KeyCode::Char('g') => self.todo_list.state.select_first(),
+            KeyCode::Char('G') => self.todo_list.state.select_last(),
+            KeyCode::Char('[') => self.jump_to(Status::Todo, false),
+            KeyCode::Char(']') => self.jump_to(Status::Todo, true),
+            KeyCode::Char('{') => self.jump_to(Status::Completed, false),
+            KeyCode::Char('}') => self.jump_to(Status::Completed, true),
+            KeyCode::Char('c') => self.perform(Action::ToggleStatus),
+            KeyCode::Char('x') => self.perform(Action::CycleStatus),
+            KeyCode::Char('d') => self.perform(Action::Delete),
+            KeyCode::Char('.') => self.repeat_action(),
+            KeyCode::Char('a') => self.add_entry(),
+            KeyCode::Char('o') => self.insert_entry_relative(true),
+            KeyCode::Char('O') => self.insert_entry_relative(false),
+            KeyCode::Char('i') => self.edit_entry(),
+            KeyCode::Char('y') => self.yank_entry(),
+            KeyCode::Char('Y') => self.yank_list(),
+            KeyCode::Char('p') => self.paste_entry(),
+            KeyCode::Char('M') => self.merge_entry(),
+            KeyCode::Char('e') => self.external = Some(External::EditJson),
+            KeyCode::Char('W') => self.use_fallback_path(),
+            KeyCode::Char('P') => match self.selected_index() {
+                Some(_) => self.external = Some(External::Pager),
+                None => self.noop(),
+            },
+            KeyCode::Char('/') => self.start_search(),
+            KeyCode::Char(':') => self.command_line = Some(Input::default()),
+            KeyCode::Char('\\') | KeyCode::Esc => self.reset_view(),
+            KeyCode::Char('n') => self.switch_view(View::Scratch),
+            KeyCode::Char(' ') => self.toggle_mark(),
+            KeyCode::Char('>') => self.start_demote(),
+            KeyCode::Char('<') => self.promote_entry(),
+            KeyCode::Char('s') => self.cycle_sort(),
+            KeyCode::Char('S') => self.sort_by_priority(),
+            KeyCode::Char('!') => self.perform(Action::CyclePriority),
+            KeyCode::Char('F') => self.toggle_json_format(),
+            KeyCode::Char('1') => self.filter_status(StatusFilter::Todo),
+            KeyCode::Char('2') => self.filter_status(StatusFilter::Completed),
+            KeyCode::Char('0') => self.filter_status(StatusFilter::All),
+            KeyCode::Char(c) if c == self.config.tag_filter_key => self.cycle_tag_filter(),
+            KeyCode::Tab => self.cycle_list(true),
+            KeyCode::BackTab => self.cycle_list(false),
+            KeyCode::Enter => self.view_entry(),
+            _ => {}
         }
     }
 
-    /// Graphically switches to the Focus above the current one
-    fn focus_up(&mut self) {
-        if let Some(focus) = &self.focus {
-            let above = match focus {
-                Focus::Todo => Focus::Todo,
-                Focus::Info => Focus::Todo,
-            };
-            self.switch_focus(above);
+    /// Responsible for handling keyboard input while typing a search query
+    fn handle_search_key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.cancel_search(),
+            KeyCode::Enter => self.commit_search(),
+            _ => {
+                self.search.handle_event(&Event::Key(key));
+                // Keep the best match selected as the query changes
+                let top = match self.visible().is_empty() {
+                    true => None,
+                    false => Some(0),
+                };
+                self.todo_list.state.select(top);
+            }
         }
     }
 
-    /// Saves the Input into the TodoItem
-    fn save_input(&mut self) {
-        let err = "Expected a selected ListItem while saving.";
-        let index = self.editing_index.expect(err);
-        let selected_item = self.todo_list.items.get_mut(index).expect(err);
-        let input = self.input.value().to_string();
-
-        if let Some(focus) = &self.focus {
-            match focus {
-                Focus::Todo => selected_item.todo = input,
-                Focus::Info => selected_item.info = input,
+    /// Responsible for handling keyboard input while typing a command
+    fn handle_command_key_event(&mut self, key: KeyEvent) {
+        let err = "Expected a command line while typing a command.";
+        match key.code {
+            KeyCode::Esc => self.command_line = None,
+            KeyCode::Enter => {
+                let input = self.command_line.take().expect(err);
+                self.run_command(input.value());
+            }
+            _ => {
+                let input = self.command_line.as_mut().expect(err);
+                input.handle_event(&Event::Key(key));
             }
         }
     }
-}
 
-// Rendering Logic
-impl App {
-    /// Renders the application to a given Frame
-    fn render(&mut self, f: &mut Frame) {
-        match self.view {
-            View::List => self.render_list_view(f),
-            View::Edit => self.render_edit_view(f),
+    /// Runs a command typed on the command line
+    fn run_command(&mut self, input: &str) {
+        let command = match command::parse(input) {
+            Ok(val) => val,
+            Err(message) => {
+                self.message = Some(message);
+                return;
+            }
+        };
+        match command {
+            LineCommand::Range { start, end, verb } => self.run_range(start, end, verb),
+            LineCommand::Export { path } => self.export_marked(&path),
+            LineCommand::List { name } => self.switch_list(name),
         }
     }
 
-    /// Renders the application in List View
-    fn render_list_view(&mut self, f: &mut Frame) {
-        let [border_area] = Layout::vertical([Constraint::Fill(1)])
-            .margin(1)
-            .areas(f.area());
-        let [inner_area] = Layout::vertical([Constraint::Fill(1)])
-            .horizontal_margin(2)
-            .vertical_margin(1)
-            .areas(border_area);
-        let list = List::new(self.todo_list.items.iter().map(|x| ListItem::from(x)))
-            .highlight_style(SELECTED_STYLE);
+    /// Applies a verb to the displayed items in a range
+    /// Endpoints outside of the list are clamped with a warning
+    fn run_range(&mut self, start: usize, end: usize, verb: Verb) {
+        let visible = self.visible();
+        if visible.is_empty() {
+            return self.noop();
+        }
+        let clamped_start = usize::max(start, 1);
+        let clamped_end = usize::min(end, visible.len());
+        if clamped_start != start || clamped_end != end {
+            let range = format!("{clamped_start},{clamped_end}");
+            self.message = Some(format!("Warning: range clamped to {range}"));
+        }
 
-        render_border(f, border_area);
-        f.render_stateful_widget(list, inner_area, &mut self.todo_list.state);
+        for &index in visible.iter().take(clamped_end).skip(clamped_start - 1) {
+            match verb {
+                Verb::Toggle => self.todo_list.items[index].toggle_status(self.clock.now()),
+            }
+        }
+        self.apply_rollups();
+        self.mark_changed();
     }
 
-    /// Renders the application in Edit View
-    fn render_edit_view(&mut self, f: &mut Frame) {
-        let err = "Expected a focus while in edit view.";
-        let focus = self.focus.clone().expect(err);
-        // Outer border
-        let bordered_area = centered_area(f.area(), 40, 15);
-        f.render_widget(
-            Block::bordered()
-                .border_type(BorderType::Rounded)
-                .fg(Color::White),
-            bordered_area,
-        );
+    /// Marks or unmarks the selected item for exporting
+    fn toggle_mark(&mut self) {
+        let Some(index) = self.selected_index() else {
+            return self.noop();
+        };
+        let id = self.todo_list.items[index].id;
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+    }
 
-        let [
-            _header_area,
-            todo_area,
-            separator_area,
-            info_area,
-            footer_area,
+    /// Marks exactly the displayed items, the hidden ones are unmarked
+    fn mark_all(&mut self) {
+        let items = &self.todo_list.items;
+        self.marked = self.visible().iter().map(|&i| items[i].id).collect();
+    }
+
+    /// Writes the marked items to a file, as JSON for .json files and markdown otherwise
+    fn export_marked(&mut self, path: &Path) {
+        let marked: Vec<&TodoItem> = self
+            .todo_list
+            .items
+            .iter()
+            .filter(|item| self.marked.contains(&item.id))
+            .collect();
+        if marked.is_empty() {
+            self.message = Some(String::from("No marked items, mark items with [Space]"));
+            return;
+        }
+        let count = marked.len();
+        let output = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => data::export_json(marked),
+            _ => Ok(data::export_markdown(marked)),
+        };
+        let written = output.and_then(|output| Ok(std::fs::write(path, output)?));
+        self.message = match written {
+            Ok(()) => Some(format!("Exported {count} items to {}", path.display())),
+            Err(err) => Some(format!("Export failed: {err}")),
+        };
+    }
+
+    /// Handles chords made of a displayed index (1-based) and an action, such as `3x`
+    /// A digit left on its own until the chord times out falls back to its status filter
+    /// Returns whether the key was part of a chord
+    fn handle_chord_key_event(&mut self, key: KeyEvent) -> bool {
+        let target = self.pending_target.take().map(|(number, _)| number);
+        match (target, key.code) {
+            (_, KeyCode::Char(c)) if c.is_ascii_digit() => {
+                let digit = c.to_digit(10).expect("Expected a digit.") as usize;
+                let number = target.unwrap_or(0).saturating_mul(10).saturating_add(digit);
+                self.pending_target = Some((number, Instant::now()));
+                self.message = Some(format!("{number}"));
+            }
+            (Some(number), KeyCode::Char('x')) => self.act_on_target(number, Action::CycleStatus),
+            (Some(number), KeyCode::Char('d')) => self.act_on_target(number, Action::Delete),
+            // Any other key cancels the chord
+            (Some(_), _) => {}
+            (None, _) => return false,
+        }
+        true
+    }
+
+    /// Selects the item at a displayed index (1-based) and performs an action on it
+    fn act_on_target(&mut self, number: usize, action: Action) {
+        match number.checked_sub(1) {
+            Some(position) if position < self.visible().len() => {
+                self.todo_list.state.select(Some(position));
+                self.perform(action);
+            }
+            _ => self.noop(),
+        }
+    }
+
+    /// Responsible for handling keyboard input while picking a parent to demote into
+    fn handle_demote_key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc => self.demoting = None,
+            KeyCode::Enter => self.finish_demote(),
+            KeyCode::Char('j') => self.select_next(),
+            KeyCode::Char('k') => self.select_previous(),
+            _ => {}
+        }
+        if self.demoting.is_some() {
+            self.message = Some(String::from(DEMOTE_PROMPT));
+        }
+    }
+
+    /// Responsible for handling keyboard input while confirming a deletion
+    fn handle_confirm_delete_key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('y') => {
+                let err = "Expected an item while confirming its deletion.";
+                let id = self.confirming_delete.take().expect(err);
+                // The item may have moved or gone since it was asked about
+                match self.position_of(id) {
+                    Some(index) if self.visible().contains(&index) => {
+                        self.select_item(index);
+                        self.delete_entry();
+                    }
+                    _ => self.noop(),
+                }
+            }
+            KeyCode::Char('n') | KeyCode::Esc => self.confirming_delete = None,
+            _ => {}
+        }
+    }
+
+    /// Responsible for handling keyboard input while confirming the completion of a parent
+    fn handle_confirm_completion_key_event(&mut self, key: KeyEvent) {
+        let err = "Expected an item while confirming its completion.";
+        let index = self.confirming_completion.take().expect(err);
+        match key.code {
+            KeyCode::Char('y') => {
+                self.complete_item(index);
+                self.mark_changed();
+            }
+            _ => self.message = Some(String::from("Not completed")),
+        }
+    }
+
+    /// Responsible for handling keyboard input in the command palette
+    fn handle_palette_key_event(&mut self, key: KeyEvent) {
+        let err = "Expected an open command palette.";
+        let palette = self.palette.as_mut().expect(err);
+        let matches = palette_matches(palette.query.value());
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => self.palette = None,
+            KeyCode::Enter => {
+                let selected = matches.get(palette.selected).copied();
+                self.palette = None;
+                match selected {
+                    Some(index) => (PALETTE_COMMANDS[index].1)(self),
+                    None => self.noop(),
+                }
+            }
+            KeyCode::Down | KeyCode::Tab => {
+                palette.selected =
+                    usize::min(palette.selected + 1, matches.len().saturating_sub(1));
+            }
+            KeyCode::Char('n') if ctrl => {
+                palette.selected =
+                    usize::min(palette.selected + 1, matches.len().saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::BackTab => palette.selected = palette.selected.saturating_sub(1),
+            KeyCode::Char('p') if ctrl => palette.selected = palette.selected.saturating_sub(1),
+            _ => {
+                palette.query.handle_event(&Event::Key(key));
+                palette.selected = 0;
+            }
+        }
+    }
+
+    /// Responsible for handling keyboard input while asking how long a completed item took
+    fn handle_time_prompt_key_event(&mut self, key: KeyEvent) {
+        let err = "Expected an input while asking for the time taken.";
+        match key.code {
+            KeyCode::Esc => self.time_prompt = None,
+            KeyCode::Enter => {
+                let (id, input) = self.time_prompt.take().expect(err);
+                // The item may have moved or gone since it was asked about
+                if let (Ok(minutes), Some(index)) = (input.value().parse(), self.position_of(id)) {
+                    self.todo_list.items[index].actual_minutes = Some(minutes);
+                    self.mark_changed();
+                }
+            }
+            KeyCode::Char(c) if !c.is_ascii_digit() => self.noop(),
+            _ => {
+                let (_, input) = self.time_prompt.as_mut().expect(err);
+                input.handle_event(&Event::Key(key));
+            }
+        }
+    }
+
+    /// Responsible for handling keyboard input in Edit View
+    /// Normal mode supports yy (yank field), p (paste), dd (clear field), x (delete character),
+    /// w/b (next/previous word), 0/$ (start/end of line), i (insert), j/k (field below/above)
+    /// and q (back), a pending operator is cancelled by any other key
+    fn handle_edit_key_event(&mut self, key: KeyEvent) {
+        // Cycling fields works in every mode
+        match key.code {
+            KeyCode::Tab => return self.cycle_focus(true),
+            KeyCode::BackTab => return self.cycle_focus(false),
+            _ => {}
+        }
+        let edit_mode = self.edit_mode.as_ref().expect("Expected an editor mode.");
+        match edit_mode {
+            EditMode::Normal => match (self.pending_key.take(), key.code) {
+                (Some('y'), KeyCode::Char('y')) => self.yank_field(),
+                (_, KeyCode::Char('y')) => self.pending_key = Some('y'),
+                (Some('d'), KeyCode::Char('d')) => self.clear_field(),
+                (_, KeyCode::Char('d')) => self.pending_key = Some('d'),
+                (_, KeyCode::Char('x')) => self.delete_char(),
+                (_, KeyCode::Char('w')) => self.jump_word(true),
+                (_, KeyCode::Char('b')) => self.jump_word(false),
+                (_, KeyCode::Char('0')) => self.jump_line(false),
+                (_, KeyCode::Char('$')) => self.jump_line(true),
+                (_, KeyCode::Char('p')) => self.paste_field(),
+                (_, KeyCode::Char('q')) => self.switch_view(View::List),
+                (_, KeyCode::Char('i')) => self.edit_mode = Some(EditMode::Insert),
+                (_, KeyCode::Char('j')) => self.focus_down(),
+                (_, KeyCode::Char('k')) => self.focus_up(),
+                // Every other key (List View ones included) is consumed here
+                _ => self.noop(),
+            },
+            EditMode::Insert => self.handle_insert_key_event(key),
+        }
+    }
+
+    /// Responsible for handling keyboard input in Insert mode (Edit View and scratchpad)
+    fn handle_insert_key_event(&mut self, key: KeyEvent) {
+        let motion = match key.code {
+            KeyCode::Esc => return self.edit_mode = Some(EditMode::Normal),
+            KeyCode::Up => Motion::Up,
+            KeyCode::Down => Motion::Down,
+            KeyCode::Left => Motion::Left,
+            KeyCode::Right => Motion::Right,
+            _ => {
+                self.input.handle_event(&Event::Key(key));
+                return;
+            }
+        };
+        if !self.config.soft_wrap_motions {
+            self.input.handle_event(&Event::Key(key));
+            return;
+        }
+        let field = self.editor_field();
+        let cursor = field.move_cursor(self.editor_area, self.input.cursor(), motion);
+        self.input = self.input.clone().with_cursor(cursor);
+    }
+
+    /// Builds the InputField being edited, laid out like it is rendered
+    fn editor_field(&self) -> InputField {
+        let mut input_field = match (&self.view, &self.focus) {
+            (View::Edit, Some(focus)) => self.input_field(self.input.value(), focus),
+            _ => InputField::new(self.input.value(), Wrap::Word),
+        };
+        input_field.set_cursor(self.input.cursor());
+        input_field
+    }
+
+    /// Responsible for handling keyboard input in Detail View
+    fn handle_detail_key_event(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.switch_view(View::List),
+            KeyCode::Char('i') => self.edit_entry(),
+            KeyCode::Char('P') => self.external = Some(External::Pager),
+            _ => {}
+        }
+    }
+
+    /// Responsible for handling keyboard input in the scratchpad
+    fn handle_scratch_key_event(&mut self, key: KeyEvent) {
+        let edit_mode = self.edit_mode.as_ref().expect("Expected an editor mode.");
+        match edit_mode {
+            EditMode::Normal => match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => self.switch_view(View::List),
+                KeyCode::Char('i') => self.edit_mode = Some(EditMode::Insert),
+                _ => {}
+            },
+            EditMode::Insert => self.handle_insert_key_event(key),
+        }
+    }
+
+    /// Starts typing a new search query
+    fn start_search(&mut self) {
+        self.search.reset();
+        self.searching = true;
+    }
+
+    /// Clears the search query and keeps the selected item selected
+    fn cancel_search(&mut self) {
+        let selected = self.selected_index();
+        self.searching = false;
+        self.search.reset();
+        match selected {
+            Some(index) => self.select_item(index),
+            None => self.todo_list.state.select(None),
+        }
+    }
+
+    /// Stops typing the search, keeping its filter and selecting the top match
+    /// Depending on the config, the top match is also opened in Detail View
+    fn commit_search(&mut self) {
+        let top = self.visible().first().copied();
+        self.searching = false;
+        if let Some(index) = top {
+            self.select_item(index);
+            if self.config.search_commit == SearchCommit::Open {
+                self.view_entry();
+            }
+        }
+    }
+
+    /// Returns the indexes of the items shown in List View, in display order
+    /// Items have to match both the search query and the status filter
+    /// While searching, matches are ranked by how closely they match the query
+    fn visible(&self) -> Vec<usize> {
+        let query = self.search.value().to_lowercase();
+        let items = &self.todo_list.items;
+        let mut visible: Vec<usize> = (0..items.len())
+            .filter(|&i| search_rank(&items[i], &query).is_some())
+            .filter(|&i| {
+                matches!(
+                    (self.status_filter, &items[i].status),
+                    (StatusFilter::All, _)
+                        | (StatusFilter::Todo, Status::Todo | Status::InProgress)
+                        | (StatusFilter::Completed, Status::Completed)
+                )
+            })
+            .filter(|&i| match &self.tag_filter {
+                Some(tag) => items[i].tags.contains(tag),
+                None => true,
+            })
+            .collect();
+        if self.searching {
+            visible.sort_by_key(|&i| search_rank(&items[i], &query));
+        } else if self.sort != Sort::Manual {
+            // Modified breaks its ties with the configured keys
+            let mut keys = match self.sort {
+                Sort::Modified => vec![SortKey::Modified],
+                Sort::Priority => vec![SortKey::Status, SortKey::Priority],
+                _ => Vec::new(),
+            };
+            keys.extend(&self.config.sort_keys);
+            // Stable, so items tied on every key keep their manual order
+            visible.sort_by(|&a, &b| compare_by(&keys, &items[a], &items[b]));
+        }
+        visible
+    }
+
+    /// Clears the search, the status filter and the sort, showing every item in order
+    /// Keeps the selected item selected, the items themselves are left untouched
+    fn reset_view(&mut self) {
+        let selected = self.selected_index();
+        self.searching = false;
+        self.search.reset();
+        self.status_filter = StatusFilter::All;
+        self.tag_filter = None;
+        self.sort = Sort::Manual;
+        if let Some(index) = selected {
+            self.select_item(index);
+        }
+    }
+
+    /// Shows only the items with a status, keeping the selection if it stays visible
+    fn filter_status(&mut self, filter: StatusFilter) {
+        let selected = self.selected_index();
+        self.status_filter = filter;
+        match selected {
+            Some(index) if self.visible().contains(&index) => self.select_item(index),
+            _ => self.todo_list.state.select_first(),
+        }
+    }
+
+    /// Switches to the next (or previous) list in alphabetical order, wrapping around
+    fn cycle_list(&mut self, forward: bool) {
+        if self.lists.is_empty() {
+            return self.noop();
+        }
+        let mut names: Vec<&String> = self.lists.keys().collect();
+        names.push(&self.list_name);
+        names.sort();
+        let err = "Expected the active list among the lists.";
+        let position = names
+            .iter()
+            .position(|&name| *name == self.list_name)
+            .expect(err);
+        let next = match forward {
+            true => (position + 1) % names.len(),
+            false => (position + names.len() - 1) % names.len(),
+        };
+        let name = names[next].clone();
+        self.switch_list(name);
+    }
+
+    /// Shows the named list, creating it if it doesn't exist yet
+    fn switch_list(&mut self, name: String) {
+        if name == self.list_name {
+            return self.noop();
+        }
+        let items = self.lists.remove(&name).unwrap_or_default();
+        let previous = std::mem::replace(&mut self.todo_list.items, items);
+        let previous_name = std::mem::replace(&mut self.list_name, name);
+        self.lists.insert(previous_name, previous);
+        // Undoing, restoring and marking only apply to the list they came from
+        self.undo_stack.clear();
+        self.last_deleted = None;
+        self.marked.clear();
+        self.tag_filter = None;
+        self.todo_list.state = ListState::default();
+        if !self.visible().is_empty() {
+            self.todo_list.state.select_first();
+        }
+        self.message = Some(format!("Switched to list '{}'", self.list_name));
+        self.mark_changed();
+    }
+
+    /// Filters by the next tag (in alphabetical order), after the last tag the filter is cleared
+    fn cycle_tag_filter(&mut self) {
+        let selected = self.selected_index();
+        let tags = distinct_tags(&self.todo_list.items);
+        self.tag_filter = match &self.tag_filter {
+            Some(current) => tags.into_iter().find(|tag| tag > current),
+            None => tags.into_iter().next(),
+        };
+        match selected {
+            Some(index) if self.visible().contains(&index) => self.select_item(index),
+            _ => self.todo_list.state.select_first(),
+        }
+    }
+
+    /// Cycles between the manual order, sorting by last modified and by the configured keys
+    /// Keeps the selected item selected
+    fn cycle_sort(&mut self) {
+        let sort = match (self.sort, self.config.sort_keys.is_empty()) {
+            // Sorting by no keys leaves the manual order, so it moves on like Manual does
+            (Sort::Manual, _) | (Sort::Keys, true) => Sort::Modified,
+            (Sort::Modified, false) => Sort::Keys,
+            (Sort::Modified, true) | (Sort::Keys, false) | (Sort::Priority, _) => Sort::Manual,
+        };
+        self.set_sort(sort);
+    }
+
+    /// Toggles between sorting by priority and the manual order
+    fn sort_by_priority(&mut self) {
+        self.set_sort(match self.sort {
+            Sort::Priority => Sort::Manual,
+            _ => Sort::Priority,
+        });
+    }
+
+    /// Changes the order of the items, keeping the selected item selected
+    fn set_sort(&mut self, sort: Sort) {
+        let selected = self.selected_index();
+        self.sort = sort;
+        self.message = Some(String::from(match self.sort {
+            Sort::Manual => "Sorted manually",
+            Sort::Modified => "Sorted by last modified",
+            Sort::Keys => "Sorted by the configured keys",
+            Sort::Priority => "Sorted by priority",
+        }));
+        if let Some(index) = selected {
+            self.select_item(index);
+        }
+    }
+
+    /// Switches between compact and pretty JSON, re-saving the todos in that format right away
+    fn toggle_json_format(&mut self) {
+        self.config.json_format = match self.config.json_format {
+            JsonFormat::Compact => JsonFormat::Pretty,
+            JsonFormat::Pretty => JsonFormat::Compact,
+        };
+        if self.save() {
+            self.message = Some(String::from(match self.config.json_format {
+                JsonFormat::Compact => "Saving compact JSON",
+                JsonFormat::Pretty => "Saving pretty JSON",
+            }));
+        }
+    }
+
+    /// Returns the index (into the items) of the selected item
+    fn selected_index(&self) -> Option<usize> {
+        let visible = self.visible();
+        let selected = self.todo_list.state.selected()?;
+        // The ListState is only clamped to the list when rendering
+        let selected = usize::min(selected, visible.len().checked_sub(1)?);
+        Some(visible[selected])
+    }
+
+    /// Selects an item by its index into the items, if it is visible
+    fn select_item(&mut self, index: usize) {
+        let position = self.visible().iter().position(|&i| i == index);
+        self.todo_list.state.select(position);
+    }
+
+    /// Marks the app for closure, unless saving would overwrite changes on file
+    /// When saving fails, quitting again closes the app without saving
+    fn exit(&mut self) {
+        if self.save() || self.exit_unsaved {
+            self.exit = true;
+        } else if !self.reload_prompt {
+            self.exit_unsaved = true;
+            let err = self.message.take().unwrap_or_default();
+            self.message = Some(format!("{err}, [q] again quits without saving"));
+        }
+    }
+
+    /// Performs a repeatable action, recording it as the last action if it changed something
+    /// (or asks for confirmation first)
+    fn perform(&mut self, action: Action) {
+        let pending = self.last_change.take();
+        match action {
+            Action::ToggleStatus => self.toggle_status(),
+            Action::CycleStatus => self.cycle_status(),
+            Action::Delete => self.confirm_delete(),
+            Action::CyclePriority => self.cycle_priority(),
+        }
+        let confirming = self.confirming_delete.is_some() || self.confirming_completion.is_some();
+        if self.last_change.is_some() || confirming {
+            self.last_action = Some(action);
+        }
+        self.last_change = self.last_change.or(pending);
+    }
+
+    /// Performs the last recorded action on the current selection
+    fn repeat_action(&mut self) {
+        match self.last_action {
+            Some(action) => self.perform(action),
+            None => self.noop(),
+        }
+    }
+
+    /// Signals that a key did nothing, giving feedback if configured
+    fn noop(&mut self) {
+        self.feedback = self.config.feedback != Feedback::Off;
+    }
+
+    /// Selects the first (or last) displayed item with a status, if there is one
+    fn jump_to(&mut self, status: Status, last: bool) {
+        match jump_position(&self.todo_list.items, &self.visible(), &status, last) {
+            Some(position) => self.todo_list.state.select(Some(position)),
+            None => self.noop(),
+        }
+    }
+
+    /// Moves the selection down, unless the last item is selected
+    fn select_next(&mut self) {
+        let len = self.visible().len();
+        match self.todo_list.state.selected() {
+            Some(i) if i + 1 >= len => self.noop(),
+            None if len == 0 => self.noop(),
+            _ => self.todo_list.state.select_next(),
+        }
+    }
+
+    /// Moves the selection up, unless the first item is selected
+    fn select_previous(&mut self) {
+        match self.todo_list.state.selected() {
+            Some(0) | None => self.noop(),
+            _ => self.todo_list.state.select_previous(),
+        }
+    }
+
+    /// Moves the selected item above the one displayed before it
+    fn move_entry_up(&mut self) {
+        self.move_entry(false);
+    }
+
+    /// Moves the selected item below the one displayed after it
+    fn move_entry_down(&mut self) {
+        self.move_entry(true);
+    }
+
+    /// Swaps the selected item with its displayed neighbor, the selection follows it
+    /// Only the manual order can be rearranged
+    fn move_entry(&mut self, down: bool) {
+        let visible = self.visible();
+        let Some(position) = self.todo_list.state.selected() else {
+            return self.noop();
+        };
+        let neighbor = match down {
+            true => position + 1,
+            false => match position.checked_sub(1) {
+                Some(val) => val,
+                None => return self.noop(),
+            },
+        };
+        match (
+            self.sort,
+            self.searching,
+            visible.get(position),
+            visible.get(neighbor),
+        ) {
+            (Sort::Manual, false, Some(&index), Some(&other)) => {
+                let items = &self.todo_list.items;
+                self.push_undo(Undo::Move(items[index].id, items[other].id));
+                self.todo_list.items.swap(index, other);
+                self.todo_list.state.select(Some(neighbor));
+                self.mark_changed();
+            }
+            _ => self.noop(),
+        }
+    }
+
+    /// Records how to reverse a change, forgetting the oldest beyond the limit
+    /// Every change that can be undone is a change to save
+    fn push_undo(&mut self, undo: Undo) {
+        self.mark_changed();
+        self.undo_stack.push(undo);
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    /// Reverses the last change, selecting the item it affected
+    fn undo(&mut self) {
+        let Some(undo) = self.undo_stack.pop() else {
+            self.message = Some(String::from("Nothing to undo"));
+            return self.noop();
+        };
+        self.mark_changed();
+        match undo {
+            Undo::Delete(index, item) => {
+                if self
+                    .last_deleted
+                    .as_ref()
+                    .is_some_and(|(_, last)| last.id == item.id)
+                {
+                    self.last_deleted = None;
+                }
+                let index = usize::min(index, self.todo_list.items.len());
+                self.todo_list.items.insert(index, item);
+                self.select_item(index);
+            }
+            Undo::Change(item) => {
+                if let Some(index) = self.position_of(item.id) {
+                    self.todo_list.items[index] = item;
+                    self.select_item(index);
+                }
+            }
+            Undo::Add(id) => {
+                if let Some(index) = self.position_of(id) {
+                    let position = self.visible().iter().position(|&i| i == index);
+                    self.todo_list.items.remove(index);
+                    if let Some(position) = position {
+                        self.select_after_delete(position);
+                    }
+                }
+            }
+            Undo::Move(id, other) => {
+                if let (Some(index), Some(other)) = (self.position_of(id), self.position_of(other))
+                {
+                    self.todo_list.items.swap(index, other);
+                    self.select_item(other);
+                }
+            }
+            Undo::Merge(item, below, other) => {
+                let id = item.id;
+                if let Some(index) = self.position_of(id) {
+                    self.todo_list.items[index] = item;
+                    let below = usize::min(below, self.todo_list.items.len());
+                    self.todo_list.items.insert(below, other);
+                }
+                if let Some(index) = self.position_of(id) {
+                    self.select_item(index);
+                }
+            }
+        }
+    }
+
+    /// Returns the index of the item with the id
+    fn position_of(&self, id: u64) -> Option<usize> {
+        self.todo_list.items.iter().position(|item| item.id == id)
+    }
+
+    /// Asks before deleting the selected item (if configured)
+    fn confirm_delete(&mut self) {
+        match (self.selected_index(), self.config.confirm_delete) {
+            (Some(index), true) => self.confirming_delete = Some(self.todo_list.items[index].id),
+            _ => self.delete_entry(),
+        }
+    }
+
+    /// Cycles the priority of the selected item
+    fn cycle_priority(&mut self) {
+        let Some(i) = self.selected_index() else {
+            return self.noop();
+        };
+        self.push_undo(Undo::Change(self.todo_list.items[i].clone()));
+        self.todo_list.items[i].cycle_priority(self.clock.now());
+        // The item may move when sorted by priority
+        self.select_item(i);
+    }
+
+    /// Toggles a TodoItem from Todo (or InProgress) to Complete or back to Todo
+    /// Completing a parent with incomplete subtasks asks for confirmation first (if configured)
+    fn toggle_status(&mut self) {
+        let Some(i) = self.selected_index() else {
+            return self.noop();
+        };
+        let item = &self.todo_list.items[i];
+        if let Status::Completed = item.status {
+            self.push_undo(Undo::Change(item.clone()));
+            self.todo_list.items[i].toggle_status(self.clock.now());
+            self.apply_rollups();
+            return;
+        }
+        let incomplete = item.incomplete_subtasks();
+        match self.config.confirm_incomplete_subtasks && incomplete > 0 {
+            true => {
+                let subtasks = match incomplete {
+                    1 => String::from("1 subtask is"),
+                    n => format!("{n} subtasks are"),
+                };
+                self.message = Some(format!("{subtasks} incomplete, complete anyway? [y/n]"));
+                self.confirming_completion = Some(i);
+            }
+            false => self.complete_item(i),
+        }
+    }
+
+    /// Cycles a TodoItem from Todo to InProgress to Completed and back to Todo
+    fn cycle_status(&mut self) {
+        let Some(i) = self.selected_index() else {
+            return self.noop();
+        };
+        // Completing goes through the same checks as toggling
+        if let Status::InProgress = self.todo_list.items[i].status {
+            return self.toggle_status();
+        }
+        self.push_undo(Undo::Change(self.todo_list.items[i].clone()));
+        let now = self.clock.now();
+        let item = &mut self.todo_list.items[i];
+        item.set_status(
+            match item.status {
+                Status::Todo => Status::InProgress,
+                _ => Status::Todo,
+            },
+            now,
+        );
+        self.apply_rollups();
+    }
+
+    /// Completes an item, along with its subtasks if configured
+    fn complete_item(&mut self, index: usize) {
+        self.push_undo(Undo::Change(self.todo_list.items[index].clone()));
+        let now = self.clock.now();
+        let item = &mut self.todo_list.items[index];
+        item.set_status(Status::Completed, now);
+        if self.config.complete_subtasks {
+            item.subtasks
+                .iter_mut()
+                .for_each(|item| item.complete_all(now));
+        }
+        self.apply_rollups();
+        if self.config.log_time_on_complete {
+            self.time_prompt = Some((self.todo_list.items[index].id, Input::default()));
+        }
+    }
+
+    /// Completes every parent whose subtasks are all completed (if configured)
+    fn apply_rollups(&mut self) {
+        if self.config.auto_complete_parents {
+            let now = self.clock.now();
+            self.todo_list
+                .items
+                .iter_mut()
+                .for_each(|item| item.rollup(now));
+        }
+    }
+
+    /// Deletes the currently selected TodoItem
+    fn delete_entry(&mut self) {
+        if let (Some(position), Some(index)) =
+            (self.todo_list.state.selected(), self.selected_index())
+        {
+            self.yank_entry();
+            let item = self.todo_list.items.remove(index);
+            self.push_undo(Undo::Delete(index, item.clone()));
+            self.last_deleted = Some((index, item));
+            self.select_after_delete(position);
+        } else {
+            self.noop();
+        }
+    }
+
+    /// Reinserts the most recently deleted TodoItem at its original index
+    fn restore_deleted(&mut self) {
+        if let Some((index, item)) = self.last_deleted.take() {
+            // Undoing the deletion again would restore it twice
+            self.undo_stack
+                .retain(|undo| !matches!(undo, Undo::Delete(_, deleted) if deleted.id == item.id));
+            let index = usize::min(index, self.todo_list.items.len());
+            self.todo_list.items.insert(index, item);
+            self.select_item(index);
+            self.mark_changed();
+        } else {
+            self.noop();
+        }
+    }
+
+    /// Selects the next or previous item (per the config) of a deleted position
+    fn select_after_delete(&mut self, position: usize) {
+        let len = self.visible().len();
+        if len == 0 {
+            self.todo_list.state.select(None);
+            return;
+        }
+        let selected = match self.config.delete_selection {
+            DeleteSelection::Next => usize::min(position, len - 1),
+            DeleteSelection::Previous => usize::min(position.saturating_sub(1), len - 1),
+        };
+        self.todo_list.state.select(Some(selected));
+    }
+
+    /// Adds a new TodoItem to the end of the list and enters Edit View
+    fn add_entry(&mut self) {
+        self.insert_entry(self.todo_list.items.len());
+    }
+
+    /// Inserts a new TodoItem below (or above) the selection and enters Edit View
+    fn insert_entry_relative(&mut self, below: bool) {
+        let index = match (self.selected_index(), below) {
+            (Some(val), true) => val + 1,
+            (Some(val), false) => val,
+            (None, true) => self.todo_list.items.len(),
+            (None, false) => 0,
+        };
+        self.insert_entry(index);
+    }
+
+    /// Clears the filters hiding a new item, or warns about them (per the config)
+    fn reveal_new_entry(&mut self) {
+        if !self.config.add_clears_filter {
+            self.message = Some(String::from(
+                "Warning: the new item is hidden by the filter",
+            ));
+            return;
+        }
+        self.searching = false;
+        self.search.reset();
+        self.status_filter = StatusFilter::All;
+        self.tag_filter = None;
+        self.message = Some(String::from("Cleared the filter to show the new item"));
+    }
+
+    /// Inserts a new TodoItem at an index and enters Edit View
+    fn insert_entry(&mut self, index: usize) {
+        if self.refuse_when_full() {
+            return;
+        }
+        let mut item = TodoItem::new(Status::Todo, "", "");
+        item.id = self.next_id();
+        self.push_undo(Undo::Add(item.id));
+        self.todo_list.items.insert(index, item);
+        self.mark_changed();
+        if !self.visible().contains(&index) {
+            self.reveal_new_entry();
+        }
+        self.input.reset();
+        self.select_item(index);
+        self.editing_index = Some(index);
+        self.switch_view(View::Edit);
+        self.edit_mode = Some(EditMode::Insert);
+    }
+
+    /// Edits an existing TodoItem
+    fn edit_entry(&mut self) {
+        if self.selected_index().is_none() {
+            return self.noop();
+        }
+        let err = "Expected a selected ListItem in Edit View.";
+        let index = self.selected_index().expect(err);
+        let selected_item = self.todo_list.items.get(index).expect(err);
+
+        self.input = Input::new(selected_item.todo.clone());
+        self.editing_index = Some(index);
+        self.switch_view(View::Edit);
+        self.edit_mode = Some(EditMode::Normal);
+    }
+
+    /// Opens the selected TodoItem in the read-only Detail View
+    fn view_entry(&mut self) {
+        match self.selected_index() {
+            Some(_) => self.switch_view(View::Detail),
+            None => self.noop(),
+        }
+    }
+
+    /// Yanks (copies) an entry if an item is selected, None otherwise
+    fn yank_entry(&mut self) {
+        let index = self.selected_index();
+        let entry = if let Some(index) = index {
+            self.todo_list.items.get(index).cloned()
+        } else {
+            None
+        };
+        self.yank_buffer = entry;
+    }
+
+    /// Copies the displayed items to the clipboard as a markdown checklist
+    fn yank_list(&mut self) {
+        let markdown = self.visible_markdown();
+        let result = copy_to_clipboard(&markdown);
+        self.finish_yank_list(result, markdown);
+    }
+
+    /// Returns the displayed items (in order) as a markdown checklist
+    fn visible_markdown(&self) -> String {
+        let items = &self.todo_list.items;
+        data::export_markdown(self.visible().into_iter().map(|i| &items[i]))
+    }
+
+    /// Keeps the markdown to print on exit when it couldn't be copied
+    fn finish_yank_list(&mut self, result: io::Result<()>, markdown: String) {
+        match result {
+            Ok(()) => self.message = Some(String::from("Copied the list as markdown")),
+            Err(err) => {
+                logging::error(format!("Failed to copy to the clipboard: {err}"));
+                self.message = Some(String::from("No clipboard, the list is printed on exit"));
+                self.unyanked = Some(markdown);
+            }
+        }
+    }
+
+    /// Pastes an entry below the selection, or index 0 if nothing is selected
+    fn paste_entry(&mut self) {
+        let mut entry = match &self.yank_buffer {
+            Some(val) => val.clone(),
+            None => return self.noop(),
+        };
+        if self.refuse_when_full() {
+            return;
+        }
+
+        let index = match self.selected_index() {
+            None => 0,
+            Some(val) => val + 1,
+        };
+
+        entry.id = self.next_id();
+        self.todo_list.items.insert(index, entry);
+        self.mark_changed();
+    }
+
+    /// Warns and returns true when the list holds the configured maximum of items
+    fn refuse_when_full(&mut self) -> bool {
+        if !data::is_full(&self.todo_list.items, &self.config) {
+            return false;
+        }
+        self.message = Some(String::from(data::FULL_MESSAGE));
+        true
+    }
+
+    /// Returns an id no item uses, in any list and subtasks included
+    fn next_id(&self) -> u64 {
+        let lists = self.lists.values().map(|items| data::max_id(items));
+        lists
+            .chain([data::max_id(&self.todo_list.items)])
+            .max()
+            .unwrap_or(0)
+            + 1
+    }
+
+    /// Starts picking a parent for the selected item to become a subtask of
+    fn start_demote(&mut self) {
+        match self.selected_index() {
+            Some(index) if self.todo_list.items.len() > 1 => {
+                self.demoting = Some(index);
+                self.message = Some(String::from(DEMOTE_PROMPT));
+            }
+            _ => self.noop(),
+        }
+    }
+
+    /// Makes the item being demoted a subtask of the selected item
+    fn finish_demote(&mut self) {
+        let (Some(child), Some(parent)) = (self.demoting, self.selected_index()) else {
+            return self.noop();
+        };
+        if child == parent {
+            return self.noop();
+        }
+        self.demoting = None;
+        let parent = demote(&mut self.todo_list.items, child, parent);
+        self.select_item(parent);
+        self.apply_rollups();
+        self.mark_changed();
+    }
+
+    /// Moves the last subtask of the selected item back into the list, below its parent
+    fn promote_entry(&mut self) {
+        let Some(parent) = self.selected_index() else {
+            return self.noop();
+        };
+        match promote(&mut self.todo_list.items, parent) {
+            Some(index) => {
+                self.select_item(index);
+                self.mark_changed();
+            }
+            None => self.noop(),
+        }
+    }
+
+    /// Merges the item displayed below the selection into the selected item
+    fn merge_entry(&mut self) {
+        let visible = self.visible();
+        let (Some(index), Some(position)) =
+            (self.selected_index(), self.todo_list.state.selected())
+        else {
+            return self.noop();
+        };
+        let Some(&below) = visible.get(position + 1) else {
+            return self.noop();
+        };
+
+        let other = self.todo_list.items.remove(below);
+        let index = if below < index { index - 1 } else { index };
+        let undo = Undo::Merge(self.todo_list.items[index].clone(), below, other.clone());
+        self.push_undo(undo);
+        self.todo_list.items[index].merge(other, self.clock.now());
+        self.select_item(index);
+    }
+
+    /// Sets the application view
+    fn switch_view(&mut self, view: View) {
+        // Do any necessary cleanup
+        match self.view {
+            View::Edit => {
+                self.save_input();
+                self.remember_focus();
+                // Edits are always written when leaving Edit View
+                if self.config.save_on_change {
+                    self.flush();
+                }
+            }
+            View::Scratch => self.save_scratch(),
+            _ => {}
+        }
+        // Do any necessary setup
+        match view {
+            View::List => {
+                self.edit_mode = None;
+                self.focus = None;
+            }
+            View::Edit => {
+                let focus = match self.config.remember_focus {
+                    true => self.remembered_focus(),
+                    false => None,
+                };
+                let focus = focus.unwrap_or(Focus::Todo);
+                if let Some(item) = self.editing_index.and_then(|i| self.todo_list.items.get(i)) {
+                    self.input = Input::new(item.field(&focus));
+                }
+                self.focus = Some(focus);
+            }
+            View::Detail => {}
+            View::Scratch => {
+                let scratch = data::read_scratch(&self.scratch_path()).unwrap_or_default();
+                self.input = Input::new(scratch);
+                self.edit_mode = Some(EditMode::Normal);
+            }
+        }
+        self.view = view;
+    }
+
+    /// Returns the path of the scratchpad, which lives next to the todos
+    fn scratch_path(&self) -> PathBuf {
+        self.data_path.with_file_name(data::SCRATCH_FILE)
+    }
+
+    /// Writes the scratchpad to file
+    fn save_scratch(&mut self) {
+        _ = data::write_scratch(self.input.value(), &self.scratch_path());
+    }
+
+    /// Records the focused field of the edited item, to return to it later
+    fn remember_focus(&mut self) {
+        let item = self.editing_index.and_then(|i| self.todo_list.items.get(i));
+        if let (Some(item), Some(focus)) = (item, &self.focus) {
+            self.focus_memory.insert(item.id, focus.clone());
+        }
+    }
+
+    /// Returns the field that was last focused on the edited item
+    fn remembered_focus(&self) -> Option<Focus> {
+        let item = self.todo_list.items.get(self.editing_index?)?;
+        self.focus_memory.get(&item.id).cloned()
+    }
+
+    /// Switches to desired 'Focus' (input field)
+    fn switch_focus(&mut self, focus: Focus) {
+        let err = "Expected a selected ListItem in Edit View.";
+        let index = self.editing_index.expect(err);
+        if self.focus == Some(Focus::Todo) && focus != Focus::Todo && !self.guard_empty_title(index)
+        {
+            return;
+        }
+        self.save_input();
+
+        let selected_item = self.todo_list.items.get(index).expect(err);
+
+        self.input = Input::new(selected_item.field(&focus));
+        self.focus = Some(focus);
+    }
+
+    /// Stops an empty title from being saved when the focus leaves it (if configured)
+    /// Returns whether the focus may leave the Todo field
+    fn guard_empty_title(&mut self, index: usize) -> bool {
+        if !self.input.value().trim().is_empty() {
+            return true;
+        }
+        let previous = &self.todo_list.items[index].todo;
+        match self.config.empty_title {
+            EmptyTitle::Allow => true,
+            EmptyTitle::Restore if !previous.trim().is_empty() => {
+                self.input = Input::new(previous.clone());
+                self.message = Some(String::from("Restored the empty title"));
+                true
+            }
+            EmptyTitle::Keep | EmptyTitle::Restore => {
+                self.message = Some(String::from("The title can't be empty"));
+                self.noop();
+                false
+            }
+        }
+    }
+
+    /// Graphically switches to the Focus below the current one
+    fn focus_down(&mut self) {
+        if let Some(focus) = &self.focus {
+            let below = match focus {
+                Focus::Todo => Focus::Tags,
+                Focus::Tags => Focus::Due,
+                Focus::Due => Focus::Info,
+                Focus::Info => Focus::Info,
+            };
+            self.switch_focus(below);
+        }
+    }
+
+    /// Graphically switches to the Focus above the current one
+    fn focus_up(&mut self) {
+        if let Some(focus) = &self.focus {
+            let above = match focus {
+                Focus::Todo => Focus::Todo,
+                Focus::Tags => Focus::Todo,
+                Focus::Due => Focus::Tags,
+                Focus::Info => Focus::Due,
+            };
+            self.switch_focus(above);
+        }
+    }
+
+    /// Switches to the next (or previous) Focus, wrapping around at the ends
+    fn cycle_focus(&mut self, forward: bool) {
+        if let Some(focus) = &self.focus {
+            let next = match (focus, forward) {
+                (Focus::Todo, true) => Focus::Tags,
+                (Focus::Tags, true) => Focus::Due,
+                (Focus::Due, true) => Focus::Info,
+                (Focus::Info, true) => Focus::Todo,
+                (Focus::Todo, false) => Focus::Info,
+                (Focus::Tags, false) => Focus::Todo,
+                (Focus::Due, false) => Focus::Tags,
+                (Focus::Info, false) => Focus::Due,
+            };
+            self.switch_focus(next);
+        }
+    }
+
+    /// Yanks (copies) the focused field's text into the field register
+    fn yank_field(&mut self) {
+        self.field_register = Some(self.input.value().to_string());
+    }
+
+    /// Clears the focused field, a title is only cleared if the empty title config allows it
+    fn clear_field(&mut self) {
+        let err = "Expected a selected ListItem in Edit View.";
+        let index = self.editing_index.expect(err);
+        let previous = std::mem::take(&mut self.input);
+        if self.focus == Some(Focus::Todo) && !self.guard_empty_title(index) {
+            self.input = previous;
+            return;
+        }
+        self.save_input();
+    }
+
+    /// Deletes the character under the cursor of the focused field
+    fn delete_char(&mut self) {
+        match self.input.handle(InputRequest::DeleteNextChar) {
+            Some(changed) if changed.value => self.save_input(),
+            _ => self.noop(),
+        }
+    }
+
+    /// Moves the cursor of the focused field to the start of the next (or previous) word
+    fn jump_word(&mut self, forward: bool) {
+        let text = self.input.value();
+        let cursor = self.input.cursor();
+        let request = match (text.is_ascii(), forward) {
+            (true, true) => InputRequest::SetCursor(widget::next_word(text, cursor)),
+            (true, false) => InputRequest::SetCursor(widget::previous_word(text, cursor)),
+            // Word wrapping's words are ASCII only, tui-input's own words will do otherwise
+            (false, true) => InputRequest::GoToNextWord,
+            (false, false) => InputRequest::GoToPrevWord,
+        };
+        self.input.handle(request);
+    }
+
+    /// Moves the cursor of the focused field to the start (or last character) of its line
+    fn jump_line(&mut self, end: bool) {
+        let chars: Vec<char> = self.input.value().chars().collect();
+        let cursor = usize::min(self.input.cursor(), chars.len());
+        let start = match chars[..cursor].iter().rposition(|&c| c == '\n') {
+            Some(newline) => newline + 1,
+            None => 0,
+        };
+        let line_end = match chars[cursor..].iter().position(|&c| c == '\n') {
+            Some(newline) => cursor + newline,
+            None => chars.len(),
+        };
+        let index = match end {
+            true => usize::max(start, line_end.saturating_sub(1)),
+            false => start,
+        };
+        self.input.handle(InputRequest::SetCursor(index));
+    }
+
+    /// Pastes the field register's text at the cursor of the focused field
+    fn paste_field(&mut self) {
+        if let Some(text) = self.field_register.clone() {
+            self.insert_text(&text);
+            self.save_input();
+        }
+    }
+
+    /// Saves the Input into the TodoItem
+    fn save_input(&mut self) {
+        let err = "Expected a selected ListItem while saving.";
+        let index = self.editing_index.expect(err);
+        let selected_item = self.todo_list.items.get_mut(index).expect(err);
+        let input = self.input.value();
+
+        if let Some(focus) = &self.focus {
+            let input = match self.config.trim_whitespace {
+                true => trim_field(focus, input),
+                false => input,
+            };
+            // Control characters would corrupt the rendering and the saved file
+            let input = widget::sanitize(input);
+            let input = match (focus, self.config.capitalize_todos) {
+                (Focus::Todo, true) => capitalize(&input),
+                _ => input,
+            };
+            // An unparsable date keeps the previous one
+            if let Focus::Due = focus
+                && parse_due(&input).is_err()
+            {
+                self.message = Some(format!("Invalid due date \"{input}\", expected YYYY-MM-DD"));
+                return;
+            }
+            selected_item.set_field(focus, input, self.clock.now());
+        }
+    }
+}
+
+// Rendering Logic
+impl App {
+    /// Renders the application to a given Frame
+    fn render(&mut self, f: &mut Frame) {
+        match self.view {
+            View::List => self.render_list_view(f),
+            View::Edit => self.render_edit_view(f),
+            View::Detail => self.render_detail_view(f),
+            View::Scratch => self.render_scratch_view(f),
+        }
+        if self.palette.is_some() {
+            self.render_palette(f);
+        }
+        if let Some(id) = self.confirming_delete {
+            self.render_confirm_delete(f, id);
+        }
+        // Flash by inverting the colors for a single frame
+        if self.feedback && self.config.feedback == Feedback::Flash {
+            let area = f.area();
+            f.buffer_mut()
+                .set_style(area, Style::new().add_modifier(Modifier::REVERSED));
+        }
+    }
+
+    /// Renders the application in List View
+    fn render_list_view(&mut self, f: &mut Frame) {
+        let [border_area] = Layout::vertical([Constraint::Fill(1)])
+            .margin(1)
+            .areas(f.area());
+        let [inner_area] = Layout::vertical([Constraint::Fill(1)])
+            .horizontal_margin(2)
+            .vertical_margin(1)
+            .areas(border_area);
+        let gauge_height = match self.config.progress_gauge {
+            true => 1,
+            false => 0,
+        };
+        let [gauge_area, inner_area] =
+            Layout::vertical([Constraint::Length(gauge_height), Constraint::Fill(1)])
+                .areas(inner_area);
+        let items = &self.todo_list.items;
+        let ratio = completion_ratio(items);
+        let gauge = LineGauge::default()
+            .ratio(ratio)
+            .filled_style(Style::new().fg(self.config.theme.palette().completed))
+            .label(format!("{:>3.0}%", ratio * 100.0));
+        let expanded = match self.config.expand_selected {
+            true => self.selected_index(),
+            false => None,
+        };
+        let today = self.clock.now().date_naive();
+        let list = List::new(self.visible().into_iter().map(|i| {
+            let item = match expanded {
+                Some(val) if val == i => expanded_item(&items[i], &self.config, inner_area.width),
+                _ => list_item(&items[i], &self.config),
+            };
+            match (
+                self.marked.contains(&items[i].id),
+                is_overdue(&items[i], today),
+                saved_query_style(&items[i], &self.config.saved_queries),
+            ) {
+                (true, _, _) => item.style(MARKED_STYLE),
+                (false, true, _) => item.style(OVERDUE_STYLE),
+                (false, false, Some(style)) => item.style(style),
+                (false, false, None) => item,
+            }
+        }))
+        .highlight_style(
+            Style::new()
+                .bg(self.config.theme.palette().selected)
+                .add_modifier(Modifier::BOLD),
+        );
+
+        render_border(f, border_area, &self.config.theme, &self.list_title());
+        f.render_widget(gauge, gauge_area);
+        f.render_stateful_widget(list, inner_area, &mut self.todo_list.state);
+        self.render_message(f);
+    }
+
+    /// Returns the List View title, with a badge counting incomplete items if configured
+    fn list_title(&self) -> String {
+        let mut title = match self.status_filter {
+            StatusFilter::All => self.config.theme.list_title.clone(),
+            StatusFilter::Todo => format!("{} [todo]", self.config.theme.list_title),
+            StatusFilter::Completed => format!("{} [done]", self.config.theme.list_title),
+        };
+        if !self.lists.is_empty() {
+            title.push_str(&format!(": {}", self.list_name));
+        }
+        if let Some(tag) = &self.tag_filter {
+            title.push_str(&format!(" #{tag}"));
+        }
+        if !self.config.count_badge {
+            return title;
+        }
+        let incomplete = self
+            .todo_list
+            .items
+            .iter()
+            .filter(|item| !matches!(item.status, Status::Completed))
+            .count();
+        match incomplete {
+            0 => format!(
+                "{title} ({})",
+                checkbox(&Status::Completed, &self.config.theme)
+            ),
+            n => format!("{title} ({n})"),
+        }
+    }
+
+    /// Renders the status message (or else the search query) on the bottom line
+    fn render_message(&self, f: &mut Frame) {
+        let [_, message_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(f.area());
+        if self.reload_prompt {
+            let prompt = "Todos changed on disk: [r]eload, [o]verwrite or [Esc] to keep yours";
+            f.render_widget(Paragraph::new(prompt).centered(), message_area);
+        } else if let Some((_, input)) = &self.time_prompt {
+            let prompt = format!("Minutes taken ([Esc] to skip): {}", input.value());
+            f.render_widget(Paragraph::new(prompt).centered(), message_area);
+        } else if let Some(message) = &self.message {
+            f.render_widget(Paragraph::new(message.as_str()).centered(), message_area);
+        } else if let Some(input) = &self.command_line {
+            let command = format!(":{}", input.value());
+            f.render_widget(Paragraph::new(command).centered(), message_area);
+        } else if let Some(error) = &self.error {
+            let error = Paragraph::new(error.as_str()).centered().style(ERROR_STYLE);
+            f.render_widget(error, message_area);
+        } else if self.searching || !self.search.value().is_empty() {
+            let query = format!("/{}", self.search.value());
+            f.render_widget(Paragraph::new(query).centered(), message_area);
+        }
+    }
+
+    /// Renders the application in Edit View
+    fn render_edit_view(&mut self, f: &mut Frame) {
+        let err = "Expected a focus while in edit view.";
+        let focus = self.focus.clone().expect(err);
+        // Outer border
+        let (width, height) = match modal_size(f.area(), &self.config.modal) {
+            Some(size) => size,
+            None => return render_resize_hint(f),
+        };
+        let bordered_area = centered_area(f.area(), width, height);
+        let theme = &self.config.theme;
+        f.render_widget(themed_block(theme, &theme.edit_title), bordered_area);
+
+        let [
+            _header_area,
+            todo_area,
+            tags_area,
+            due_area,
+            separator_area,
+            info_area,
+            footer_area,
         ] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
             Constraint::Length(1),
             Constraint::Length(1),
             Constraint::Length(1),
@@ -351,124 +2327,2808 @@ impl App {
         .horizontal_margin(1)
         .areas(bordered_area);
 
-        f.render_widget(
-            Block::bordered()
-                .borders(Borders::BOTTOM)
-                .border_type(BorderType::Plain)
-                .fg(Color::White),
-            separator_area,
+        f.render_widget(separator_block(&self.config.theme), separator_area);
+
+        // Handle the focused area
+        let input_field = self.editor_field();
+        let focus_area = match focus {
+            Focus::Todo => todo_area,
+            Focus::Tags => tags_area,
+            Focus::Due => due_area,
+            Focus::Info => info_area,
+        };
+        self.editor_area = focus_area;
+        f.render_widget(&input_field, focus_area);
+        if let Focus::Due = focus {
+            f.render_widget(due_hint(self.input.value()).right_aligned(), due_area);
+        }
+
+        // Handle the non focused areas
+        let err = "Expected a selected ListItem in Edit View.";
+        let index = self.editing_index.expect(err);
+        let selected_item = self.todo_list.items.get(index).expect(err);
+        let fields = [
+            (Focus::Todo, todo_area),
+            (Focus::Tags, tags_area),
+            (Focus::Due, due_area),
+            (Focus::Info, info_area),
+        ];
+        for (field, area) in fields.into_iter().filter(|(field, _)| *field != focus) {
+            let text = selected_item.field(&field);
+            f.render_widget(&self.input_field(text, &field), area);
+        }
+
+        // Footer area
+        let edit_mode = self.edit_mode.as_ref().expect("Expected an editor mode.");
+        let footer = editor_footer(edit_mode, &self.view, footer_area.width);
+        f.render_widget(Paragraph::new(footer), footer_area);
+
+        // Render cursor
+        render_cursor(
+            f,
+            input_field.get_cursor_at(focus_area, self.input.cursor()),
+        );
+    }
+
+    /// Builds the InputField for a field, with a prompt if configured
+    fn input_field<T: Into<String>>(&self, text: T, focus: &Focus) -> InputField {
+        // Single line fields scroll horizontally instead of wrapping
+        let wrapping = match focus {
+            Focus::Todo | Focus::Tags | Focus::Due => Wrap::None,
+            Focus::Info => self.config.edit_wrap.into(),
+        };
+        let mut input_field = InputField::new(text, wrapping);
+        if let Focus::Info = focus {
+            input_field.set_line_numbers(self.config.info_line_numbers);
+        }
+        let theme = &self.config.theme;
+        if theme.prompts {
+            let prompt = match focus {
+                Focus::Todo => "Todo: ",
+                Focus::Tags => "Tags: ",
+                Focus::Due => "Due:  ",
+                Focus::Info => "Info: ",
+            };
+            input_field.set_prompt(prompt, Style::new().fg(theme.prompt_color));
+        }
+        input_field
+    }
+
+    /// Renders the scratchpad, a free-form note separate from the todos
+    fn render_scratch_view(&mut self, f: &mut Frame) {
+        let [border_area] = Layout::vertical([Constraint::Fill(1)])
+            .margin(1)
+            .areas(f.area());
+        let [text_area, footer_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)])
+                .horizontal_margin(2)
+                .vertical_margin(1)
+                .areas(border_area);
+        f.render_widget(themed_block(&self.config.theme, "Scratchpad"), border_area);
+
+        let input_field = self.editor_field();
+        self.editor_area = text_area;
+        f.render_widget(&input_field, text_area);
+
+        let edit_mode = self.edit_mode.as_ref().expect("Expected an editor mode.");
+        let footer = editor_footer(edit_mode, &self.view, footer_area.width);
+        f.render_widget(Paragraph::new(footer), footer_area);
+        render_cursor(f, input_field.get_cursor_at(text_area, self.input.cursor()));
+    }
+
+    /// Renders a small popup asking whether to delete an item
+    fn render_confirm_delete(&self, f: &mut Frame, id: u64) {
+        let Some(index) = self.position_of(id) else {
+            return;
+        };
+        let item = &self.todo_list.items[index];
+        let max_width = f.area().width.saturating_sub(4) as usize;
+        let title = widget::truncate(&item.todo, max_width.saturating_sub(16));
+        let prompt = format!("Delete '{title}'? (y/n)");
+        let width = u16::min(prompt.chars().count() as u16 + 4, f.area().width);
+        let area = centered_area(f.area(), width, 3);
+        f.render_widget(Clear, area);
+        f.render_widget(
+            Paragraph::new(prompt)
+                .centered()
+                .block(themed_block(&self.config.theme, "Delete")),
+            area,
+        );
+    }
+
+    /// Renders the command palette over the List View, with the query above the matches
+    fn render_palette(&self, f: &mut Frame) {
+        let err = "Expected an open command palette.";
+        let palette = self.palette.as_ref().expect(err);
+        let area = centered_area(f.area(), 40, 14);
+        let block = themed_block(&self.config.theme, "Commands");
+        let [query_area, list_area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(block.inner(area));
+        f.render_widget(Clear, area);
+        f.render_widget(block, area);
+        f.render_widget(
+            Paragraph::new(format!("> {}", palette.query.value())),
+            query_area,
+        );
+
+        let names = palette_matches(palette.query.value())
+            .into_iter()
+            .map(|index| PALETTE_COMMANDS[index].0);
+        let list = List::new(names).highlight_style(
+            Style::new()
+                .bg(self.config.theme.palette().selected)
+                .add_modifier(Modifier::BOLD),
+        );
+        let mut state = ListState::default().with_selected(Some(palette.selected));
+        f.render_stateful_widget(list, list_area, &mut state);
+    }
+
+    /// Renders the selected TodoItem with its info as markdown
+    fn render_detail_view(&mut self, f: &mut Frame) {
+        let err = "Expected a selected ListItem in Detail View.";
+        let index = self.selected_index().expect(err);
+        let selected_item = self.todo_list.items.get(index).expect(err);
+
+        let area = centered_area(f.area(), 60, 20);
+        let block = themed_block(&self.config.theme, &selected_item.todo);
+        let [inner_area] = Layout::vertical([Constraint::Fill(1)])
+            .horizontal_margin(1)
+            .areas(block.inner(area));
+
+        f.render_widget(block, area);
+        // Highlight the search query, if the item was opened while searching
+        let text = markdown::highlight(
+            markdown::to_text(&selected_item.info),
+            self.search.value(),
+            markdown::HIGHLIGHT_STYLE,
+        );
+        f.render_widget(
+            Paragraph::new(text).wrap(ratatui::widgets::Wrap { trim: false }),
+            inner_area,
+        );
+        if self.config.hyperlinks {
+            widget::linkify(inner_area, f.buffer_mut());
+        }
+    }
+}
+
+impl TodoItem {
+    pub fn new(status: Status, todo: &str, info: &str) -> Self {
+        TodoItem::builder()
+            .status(status)
+            .todo(todo)
+            .info(info)
+            .build()
+    }
+
+    /// Starts building an item, every field left unset is empty
+    pub fn builder() -> TodoItemBuilder {
+        TodoItemBuilder::default()
+    }
+
+    /// Merges another item into this one
+    /// Titles are joined by a space, infos by a newline and tags are combined
+    /// The merged item is only completed if both items were
+    fn merge(&mut self, other: TodoItem, now: DateTime<Local>) {
+        self.touch(now);
+        self.todo = [self.todo.as_str(), other.todo.as_str()].join(" ");
+        self.info = [self.info.as_str(), other.info.as_str()]
+            .into_iter()
+            .filter(|info| !info.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        for tag in other.tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+        self.subtasks.extend(other.subtasks);
+        if matches!(self.status, Status::Completed) && !matches!(other.status, Status::Completed) {
+            self.set_status(other.status, now);
+        }
+    }
+
+    /// Returns the text of the field edited by an input field
+    fn field(&self, focus: &Focus) -> String {
+        match focus {
+            Focus::Todo => self.todo.clone(),
+            Focus::Tags => self.tags.join(" "),
+            Focus::Due => self.due.map(|due| due.to_string()).unwrap_or_default(),
+            Focus::Info => self.info.clone(),
+        }
+    }
+
+    /// Sets a field from the text of an input field
+    fn set_field(&mut self, focus: &Focus, text: String, now: DateTime<Local>) {
+        if self.field(focus) != text {
+            self.touch(now);
+        }
+        match focus {
+            Focus::Todo => self.todo = text,
+            Focus::Tags => {
+                self.tags = text
+                    .split_whitespace()
+                    .map(|tag| tag.trim_start_matches('#').to_string())
+                    .filter(|tag| !tag.is_empty())
+                    .collect();
+            }
+            Focus::Due => {
+                if let Ok(due) = parse_due(&text) {
+                    self.due = due;
+                }
+            }
+            Focus::Info => self.info = text,
+        }
+    }
+
+    /// Sets the status, recording when the item was completed
+    pub fn set_status(&mut self, status: Status, now: DateTime<Local>) {
+        if std::mem::discriminant(&self.status) != std::mem::discriminant(&status) {
+            self.touch(now);
+        }
+        self.completed_at = match status {
+            Status::Completed => self.completed_at.or(Some(now)),
+            Status::Todo | Status::InProgress => None,
+        };
+        self.status = status;
+    }
+
+    /// Sets the info, counting as a modification if it changed
+    pub fn set_info(&mut self, info: String, now: DateTime<Local>) {
+        self.set_field(&Focus::Info, info, now);
+    }
+
+    /// Cycles the priority from Medium to High to Low
+    fn cycle_priority(&mut self, now: DateTime<Local>) {
+        self.priority = match self.priority {
+            Priority::Low => Priority::Medium,
+            Priority::Medium => Priority::High,
+            Priority::High => Priority::Low,
+        };
+        self.touch(now);
+    }
+
+    /// Toggles from Todo (or InProgress) to Complete or back to Todo
+    fn toggle_status(&mut self, now: DateTime<Local>) {
+        self.set_status(
+            match self.status {
+                Status::Todo | Status::InProgress => Status::Completed,
+                Status::Completed => Status::Todo,
+            },
+            now,
+        );
+    }
+
+    /// Records that the item was modified at `now`
+    fn touch(&mut self, now: DateTime<Local>) {
+        self.modified_at = Some(now);
+    }
+
+    /// Returns (completed, total) subtasks, or None if there are no subtasks
+    fn progress(&self) -> Option<(usize, usize)> {
+        if self.subtasks.is_empty() {
+            return None;
+        }
+        let completed = self
+            .subtasks
+            .iter()
+            .filter(|item| matches!(item.status, Status::Completed))
+            .count();
+        Some((completed, self.subtasks.len()))
+    }
+
+    /// Returns how many subtasks (at any depth) are not completed
+    fn incomplete_subtasks(&self) -> usize {
+        self.subtasks
+            .iter()
+            .map(|item| match item.status {
+                Status::Todo | Status::InProgress => 1 + item.incomplete_subtasks(),
+                Status::Completed => item.incomplete_subtasks(),
+            })
+            .sum()
+    }
+
+    /// Completes this item and all of its subtasks (at any depth)
+    fn complete_all(&mut self, now: DateTime<Local>) {
+        self.set_status(Status::Completed, now);
+        self.subtasks
+            .iter_mut()
+            .for_each(|item| item.complete_all(now));
+    }
+
+    /// Completes this item (and nested parents) when all of its subtasks are completed
+    fn rollup(&mut self, now: DateTime<Local>) {
+        self.subtasks.iter_mut().for_each(|item| item.rollup(now));
+        if let Some((completed, total)) = self.progress()
+            && completed == total
+        {
+            self.set_status(Status::Completed, now);
+        }
+    }
+}
+
+/// Builds a TodoItem one field at a time
+#[derive(Default)]
+pub struct TodoItemBuilder {
+    id: u64,
+    status: Status,
+    todo: String,
+    info: String,
+    subtasks: Vec<TodoItem>,
+    tags: Vec<String>,
+    due: Option<NaiveDate>,
+    priority: Priority,
+}
+
+impl TodoItemBuilder {
+    pub fn id(mut self, id: u64) -> Self {
+        self.id = id;
+        self
+    }
+
+    pub fn status(mut self, status: Status) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn todo<T: Into<String>>(mut self, todo: T) -> Self {
+        self.todo = todo.into();
+        self
+    }
+
+    pub fn info<T: Into<String>>(mut self, info: T) -> Self {
+        self.info = info.into();
+        self
+    }
+
+    pub fn subtasks(mut self, subtasks: Vec<TodoItem>) -> Self {
+        self.subtasks = subtasks;
+        self
+    }
+
+    /// Sets the tags, without their leading '#'
+    pub fn tags<T: Into<String>>(mut self, tags: impl IntoIterator<Item = T>) -> Self {
+        self.tags = tags.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn due(mut self, due: NaiveDate) -> Self {
+        self.due = Some(due);
+        self
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn build(self) -> TodoItem {
+        TodoItem {
+            id: self.id,
+            status: self.status,
+            todo: self.todo,
+            info: self.info,
+            subtasks: self.subtasks,
+            completed_at: None,
+            tags: self.tags,
+            modified_at: None,
+            due: self.due,
+            actual_minutes: None,
+            priority: self.priority,
+        }
+    }
+}
+
+impl FromIterator<(Status, &'static str, &'static str)> for TodoList {
+    fn from_iter<I: IntoIterator<Item = (Status, &'static str, &'static str)>>(iter: I) -> Self {
+        let items = iter
+            .into_iter()
+            .map(|(status, todo, info)| TodoItem::new(status, todo, info))
+            .collect();
+        let state = ListState::default();
+        Self { items, state }
+    }
+}
+
+/// Converts a TodoItem into a ListItem as configured
+fn list_item(value: &TodoItem, config: &Config) -> ListItem<'static> {
+    ListItem::new(list_line(value, config))
+}
+
+/// Creates a ListItem with the info wrapped below the title
+fn expanded_item(value: &TodoItem, config: &Config, width: u16) -> ListItem<'static> {
+    let mut lines = vec![list_line(value, config)];
+    lines.extend(expanded_lines(&value.info, width, config.preview_wrap));
+    ListItem::new(lines)
+}
+
+/// Builds the InputField laying out the info preview in List View
+fn preview_field(info: &str, wrap: WrapMode) -> InputField {
+    // Word wrapping only supports ASCII, anything else is shown unwrapped
+    let wrap = match info.is_ascii() {
+        true => wrap,
+        false => WrapMode::None,
+    };
+    InputField::new(info, wrap.into())
+}
+
+/// Wraps the info to the width (per the config), indenting it under the title
+fn expanded_lines(info: &str, width: u16, wrap: WrapMode) -> Vec<Line<'static>> {
+    let indent = "  ";
+    let width = u16::max(width.saturating_sub(indent.len() as u16), 1);
+    let area = Rect::new(0, 0, width, u16::MAX);
+    preview_field(info, wrap)
+        .lines(area)
+        .into_iter()
+        .map(|line| Line::raw(format!("{indent}{line}")))
+        .collect()
+}
+
+/// Returns the glyph showing a status, which is plain ASCII in ascii mode
+fn checkbox(status: &Status, theme: &Theme) -> &'static str {
+    match (status, theme.ascii == Some(true)) {
+        (Status::Todo, false) => "☐",
+        (Status::InProgress, false) => "◐",
+        (Status::Completed, false) => "✓",
+        (Status::Todo, true) => "[ ]",
+        (Status::InProgress, true) => "[~]",
+        (Status::Completed, true) => "[x]",
+    }
+}
+
+/// Returns the marker showing a priority and its color, which is plain ASCII in ascii mode
+fn priority_marker(priority: Priority, theme: &Theme) -> Span<'static> {
+    let marker = match (priority, theme.ascii == Some(true)) {
+        (Priority::High, _) => "!",
+        (Priority::Medium, false) => "·",
+        (Priority::Medium, true) => ".",
+        (Priority::Low, _) => "-",
+    };
+    let color = match priority {
+        Priority::High => Color::Red,
+        Priority::Medium => Color::Yellow,
+        Priority::Low => Color::Blue,
+    };
+    Span::styled(format!("{marker} "), Style::new().fg(color))
+}
+
+/// Creates the title line of a TodoItem, with its priority, progress and tags
+fn list_line(value: &TodoItem, config: &Config) -> Line<'static> {
+    let rollup = match value.progress() {
+        Some((completed, total)) => format!(" ({completed}/{total})"),
+        None => String::new(),
+    };
+    let checkbox = checkbox(&value.status, &config.theme);
+    let text = match value.status {
+        Status::Todo => Span::raw(format!("{checkbox} {}{rollup}", value.todo)),
+        Status::InProgress => Span::styled(
+            format!("{checkbox} {}{rollup}", value.todo),
+            Style::new().fg(config.theme.palette().in_progress),
+        ),
+        Status::Completed => Span::styled(
+            format!("{checkbox} {}{rollup}", value.todo),
+            Style::new().fg(config.theme.palette().completed),
+        ),
+    };
+    let mut spans = vec![priority_marker(value.priority, &config.theme), text];
+    for tag in display_tags(&value.tags, config.tag_order, &config.priority_tags) {
+        spans.push(Span::styled(format!(" #{tag}"), TAG_STYLE));
+    }
+    if let Some(due) = value.due {
+        spans.push(Span::styled(format!("  due {due}"), DUE_STYLE));
+    }
+    // Preview the first line of the info, cut to the configured width
+    if let (Some(width), Some(first_line)) = (config.preview_width, value.info.lines().next())
+        && !first_line.trim().is_empty()
+    {
+        let preview = widget::truncate(first_line.trim(), width);
+        spans.push(Span::styled(format!("  {preview}"), PREVIEW_STYLE));
+    }
+    Line::from(spans)
+}
+
+/// Parses the text of the due date field, an empty field clears the due date
+fn parse_due(text: &str) -> Result<Option<NaiveDate>, chrono::ParseError> {
+    match text.trim() {
+        "" => Ok(None),
+        text => NaiveDate::parse_from_str(text, "%Y-%m-%d").map(Some),
+    }
+}
+
+/// Describes the date being typed into the due date field, or why it doesn't parse
+fn due_hint(text: &str) -> Line<'static> {
+    match parse_due(text) {
+        Ok(Some(due)) => Line::styled(due.format("%A, %B %-d, %Y").to_string(), PREVIEW_STYLE),
+        Ok(None) => Line::default(),
+        Err(_) => Line::styled("not a date (YYYY-MM-DD)", OVERDUE_STYLE),
+    }
+}
+
+/// Decides whether an incomplete item's due date has passed
+fn is_overdue(item: &TodoItem, today: NaiveDate) -> bool {
+    !matches!(item.status, Status::Completed) && item.due.is_some_and(|due| due < today)
+}
+
+/// Returns the editor's mode followed by the keys that work in it, cut to the width
+/// The footer is padded by a blank column on either side, so the text gets one less than the width
+fn editor_footer(edit_mode: &EditMode, view: &View, width: u16) -> String {
+    let footer = match (edit_mode, view) {
+        (EditMode::Normal, View::Edit) => " NORMAL Mode  i:insert q:back Tab:field",
+        (EditMode::Normal, _) => " NORMAL Mode  i:insert q:back",
+        (EditMode::Insert, _) => " INSERT Mode  Esc:normal",
+    };
+    widget::truncate(footer, (width as usize).saturating_sub(1))
+}
+
+/// Returns the commands (indexes into PALETTE_COMMANDS) matching the query, best first
+fn palette_matches(query: &str) -> Vec<usize> {
+    let mut matches: Vec<(usize, usize)> = PALETTE_COMMANDS
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (name, _))| Some((fuzzy_score(name, query)?, index)))
+        .collect();
+    // Stable, so equally good matches keep the listed order
+    matches.sort_by_key(|&(score, _)| score);
+    matches.into_iter().map(|(_, index)| index).collect()
+}
+
+/// Scores how well the query's characters appear in order within the name, lower is better
+/// Returns None if they don't all appear, gaps between the characters cost a point each
+fn fuzzy_score(name: &str, query: &str) -> Option<usize> {
+    let mut name = name.chars().map(|c| c.to_ascii_lowercase());
+    let mut score = 0;
+    let mut started = false;
+    for q in query.chars().filter(|c| !c.is_whitespace()) {
+        let q = q.to_ascii_lowercase();
+        let skipped = name.by_ref().position(|c| c == q)?;
+        // Where the match starts doesn't matter, only how spread out it is
+        if started {
+            score += skipped;
+        }
+        started = true;
+    }
+    Some(score)
+}
+
+/// Returns every tag used by the items, sorted and without duplicates
+fn distinct_tags(items: &[TodoItem]) -> Vec<String> {
+    let tags: BTreeSet<&String> = items.iter().flat_map(|item| &item.tags).collect();
+    tags.into_iter().cloned().collect()
+}
+
+/// Moves an item into the subtasks of another item
+/// Returns the index of the parent after the move
+fn demote(items: &mut Vec<TodoItem>, child: usize, parent: usize) -> usize {
+    let item = items.remove(child);
+    let parent = if child < parent { parent - 1 } else { parent };
+    items[parent].subtasks.push(item);
+    parent
+}
+
+/// Moves the last subtask of an item into the list, directly below the item
+/// Returns the index of the promoted item, or None if there are no subtasks
+fn promote(items: &mut Vec<TodoItem>, parent: usize) -> Option<usize> {
+    let item = items[parent].subtasks.pop()?;
+    items.insert(parent + 1, item);
+    Some(parent + 1)
+}
+
+/// Returns the fraction of items that are completed, an empty list counts as 0
+fn completion_ratio(items: &[TodoItem]) -> f64 {
+    if items.is_empty() {
+        return 0.0;
+    }
+    let completed = items
+        .iter()
+        .filter(|item| matches!(item.status, Status::Completed))
+        .count();
+    completed as f64 / items.len() as f64
+}
+
+/// Returns the position (among the displayed items) of the first or last item with a status
+fn jump_position(
+    items: &[TodoItem],
+    visible: &[usize],
+    status: &Status,
+    last: bool,
+) -> Option<usize> {
+    let matches = |&(_, &i): &(usize, &usize)| items[i].status == *status;
+    let found = match last {
+        true => visible.iter().enumerate().rev().find(matches),
+        false => visible.iter().enumerate().find(matches),
+    };
+    found.map(|(position, _)| position)
+}
+
+/// Orders items by each key in turn, until one of them tells the items apart
+fn compare_by(keys: &[SortKey], a: &TodoItem, b: &TodoItem) -> Ordering {
+    keys.iter().fold(Ordering::Equal, |ordering, key| {
+        ordering.then_with(|| match key {
+            SortKey::Modified => by_modified(a, b),
+            SortKey::Status => by_status(a, b),
+            SortKey::Due => by_due(a, b),
+            SortKey::Priority => b.priority.cmp(&a.priority),
+            SortKey::Title => a.todo.to_lowercase().cmp(&b.todo.to_lowercase()),
+        })
+    })
+}
+
+/// Orders items from least to most recently modified, never modified items first
+fn by_modified(a: &TodoItem, b: &TodoItem) -> Ordering {
+    a.modified_at.cmp(&b.modified_at)
+}
+
+/// Orders incomplete items before completed ones
+fn by_status(a: &TodoItem, b: &TodoItem) -> Ordering {
+    let rank = |item: &TodoItem| matches!(item.status, Status::Completed);
+    rank(a).cmp(&rank(b))
+}
+
+/// Orders items from soonest to latest due, items without a due date last
+fn by_due(a: &TodoItem, b: &TodoItem) -> Ordering {
+    match (a.due, b.due) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Writes one line per item (like the List View) without any styling
+fn plaintext_summary(items: &[TodoItem]) -> String {
+    let mut summary = String::new();
+    for item in items {
+        let glyph = match item.status {
+            Status::Todo => '☐',
+            Status::InProgress => '◐',
+            Status::Completed => '✓',
+        };
+        summary.push_str(&format!("{glyph} {}", item.todo));
+        if let Some((completed, total)) = item.progress() {
+            summary.push_str(&format!(" ({completed}/{total})"));
+        }
+        for tag in &item.tags {
+            summary.push_str(&format!(" #{tag}"));
+        }
+        summary.push('\n');
+    }
+    summary
+}
+
+/// Orders tags for display, leaving the stored order intact
+/// Priority tags come first (in the priority order), followed by the rest alphabetically
+fn display_tags<'a>(tags: &'a [String], order: TagOrder, priority: &[String]) -> Vec<&'a str> {
+    let mut tags: Vec<&str> = tags.iter().map(String::as_str).collect();
+    match order {
+        TagOrder::Insertion => {}
+        TagOrder::Alphabetical => tags.sort(),
+        TagOrder::Priority => {
+            let rank = |tag: &str| priority.iter().position(|p| p == tag);
+            // None sorts before Some, so map the unranked tags to the end
+            tags.sort_by_key(|&tag| (rank(tag).unwrap_or(usize::MAX), tag));
+        }
+    }
+    tags
+}
+
+/// Leaves the TUI to run a task (such as an external program), then restores it
+fn suspend<T>(terminal: &mut DefaultTerminal, task: impl FnOnce() -> T) -> Result<T> {
+    ratatui::restore();
+    let result = task();
+    terminal::enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen, EnableBracketedPaste)?;
+    terminal.clear()?;
+    Ok(result)
+}
+
+/// Shows text in the user's $PAGER (less if unset) and waits for it to close
+fn open_pager(text: &str) -> io::Result<()> {
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| String::from("less"));
+    pipe_to(&pager, text)
+}
+
+/// Copies text to the system clipboard with the first clipboard tool that works
+fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut result = Err(io::Error::other("No clipboard tool found."));
+    for tool in CLIPBOARD_TOOLS {
+        let mut parts = tool.split_whitespace();
+        let Some(program) = parts.next() else {
+            continue;
+        };
+        let mut command = Command::new(program);
+        // Their errors would draw over the TUI
+        command
+            .args(parts)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        result = feed(command, text);
+        if result.is_ok() {
+            break;
+        }
+    }
+    result
+}
+
+/// Runs a command line (split on whitespace) with the text as its input
+fn pipe_to(command: &str, text: &str) -> io::Result<()> {
+    let mut parts = command.split_whitespace();
+    let Some(program) = parts.next() else {
+        return Err(io::Error::other("Empty command."));
+    };
+    let mut command = Command::new(program);
+    command.args(parts);
+    feed(command, text)
+}
+
+/// Runs a command with the text as its input and waits for it to exit
+fn feed(mut command: Command, text: &str) -> io::Result<()> {
+    let mut child = command.stdin(Stdio::piped()).spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A pager quit before reading everything closes the pipe early, that's fine
+        _ = stdin.write_all(text.as_bytes());
+    }
+    if !child.wait()?.success() {
+        return Err(io::Error::other("Command exited with an error."));
+    }
+    Ok(())
+}
+
+/// Opens a file in the user's $EDITOR (vi if unset) and waits for it to close
+fn open_editor(path: &Path) -> io::Result<()> {
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| String::from("vi"));
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = Command::new(program).args(parts).arg(path).status()?;
+    if !status.success() {
+        return Err(io::Error::other("Editor exited with an error."));
+    }
+    Ok(())
+}
+
+/// Renders the cursor as needed
+fn render_cursor(f: &mut Frame, pos: (u16, u16)) {
+    f.set_cursor_position(pos)
+}
+
+/// Helper function to make a centered area of any size
+fn centered_area(area: Rect, x: u16, y: u16) -> Rect {
+    let vertical = Layout::vertical([Constraint::Length(y)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Length(x)]).flex(Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    area
+}
+
+/// Trims the whitespace surrounding a field's text
+/// Newlines in the info are kept, as they may be intentional formatting
+fn trim_field<'a>(focus: &Focus, text: &'a str) -> &'a str {
+    match focus {
+        Focus::Info => text.trim_matches([' ', '\t']),
+        _ => text.trim(),
+    }
+}
+
+/// Uppercases the first letter of the text
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Ranks how well an item matches a lowercase query, lower is better
+/// Returns None if the item doesn't match at all
+fn search_rank(item: &TodoItem, query: &str) -> Option<u8> {
+    let todo = item.todo.to_lowercase();
+    if todo == query {
+        Some(0)
+    } else if todo.starts_with(query) {
+        Some(1)
+    } else if todo.contains(query) {
+        Some(2)
+    } else if item.info.to_lowercase().contains(query) {
+        Some(3)
+    } else {
+        None
+    }
+}
+
+/// Splits a pasted block into its first non-blank line (the title) and the remaining lines
+/// Blank lines are dropped and every line is trimmed
+fn split_paste(text: &str) -> (String, String) {
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    let title = lines.next().unwrap_or_default().to_string();
+    let rest = lines.collect::<Vec<_>>().join("\n");
+    (title, rest)
+}
+
+/// Returns the style of the first saved query the item matches, if any
+fn saved_query_style(item: &TodoItem, queries: &[SavedQuery]) -> Option<Style> {
+    queries
+        .iter()
+        .find(|query| matches_query(item, query))
+        .map(|query| Style::new().fg(query.color))
+}
+
+/// Checks whether an item matches every field set on a saved query
+fn matches_query(item: &TodoItem, query: &SavedQuery) -> bool {
+    let tag = match &query.tag {
+        Some(tag) => item.tags.contains(tag),
+        None => true,
+    };
+    let text = match &query.text {
+        Some(text) => search_rank(item, &text.to_lowercase()).is_some(),
+        None => true,
+    };
+    let status = matches!(
+        (query.status, &item.status),
+        (None, _)
+            | (Some(QueryStatus::Todo), Status::Todo | Status::InProgress)
+            | (Some(QueryStatus::InProgress), Status::InProgress)
+            | (Some(QueryStatus::Completed), Status::Completed)
+    );
+    tag && text && status
+}
+
+/// Decides whether the user has been idle long enough to auto-save
+/// Only saves once per period of activity, as the activity is cleared on save
+fn should_auto_save(last_activity: Option<Instant>, now: Instant, idle: Option<Duration>) -> bool {
+    match (last_activity, idle) {
+        (Some(last_activity), Some(idle)) => now.duration_since(last_activity) >= idle,
+        _ => false,
+    }
+}
+
+/// Decides whether to notify about an item due within the window (in days) of today
+/// Overdue items count as due soon, items that were already notified never do
+fn should_notify(due: NaiveDate, today: NaiveDate, window: i64, notified: bool) -> bool {
+    !notified && (due - today).num_days() <= window
+}
+
+/// Decides whether changes have settled for the debounce window, coalescing bursts
+/// Every change restarts the window, as it replaces the last change
+fn should_flush(last_change: Option<Instant>, now: Instant, debounce: Duration) -> bool {
+    match last_change {
+        Some(last_change) => now.duration_since(last_change) >= debounce,
+        None => false,
+    }
+}
+
+/// Computes the Edit View modal size for a terminal area within the given bounds
+/// Returns None when the area is smaller than the minimum size
+fn modal_size(area: Rect, bounds: &ModalSize) -> Option<(u16, u16)> {
+    if area.width < bounds.min_width || area.height < bounds.min_height {
+        return None;
+    }
+    // Leave some of the list visible around the modal
+    let width = area.width.saturating_sub(4).min(bounds.max_width);
+    let height = area.height.saturating_sub(2).min(bounds.max_height);
+    let width = width.max(bounds.min_width).min(area.width);
+    let height = height.max(bounds.min_height).min(area.height);
+    Some((width, height))
+}
+
+/// Renders a hint asking the user to enlarge their terminal
+fn render_resize_hint(f: &mut Frame) {
+    let [area] = Layout::vertical([Constraint::Length(1)])
+        .flex(Flex::Center)
+        .areas(f.area());
+    f.render_widget(
+        Paragraph::new("Terminal too small, please resize").centered(),
+        area,
+    );
+}
+
+/// Builds a bordered Block styled by the theme, with a centered title if non-empty
+fn themed_block<'a>(theme: &Theme, title: &str) -> Block<'a> {
+    let block = Block::bordered()
+        .border_type(theme.border.into())
+        .fg(theme.palette().border);
+    let block = match theme.ascii == Some(true) {
+        true => block.border_set(ASCII_BORDER),
+        false => block,
+    };
+    match title.is_empty() {
+        true => block,
+        false => block.title(Line::raw(format!(" {title} ")).centered()),
+    }
+}
+
+/// Creates the line separating the Due and Info fields, styled by the theme
+fn separator_block<'a>(theme: &Theme) -> Block<'a> {
+    let block = Block::bordered()
+        .borders(Borders::BOTTOM)
+        .fg(theme.separator_color.unwrap_or(theme.palette().border));
+    let block = match (theme.ascii == Some(true), theme.separator) {
+        (true, _) => block.border_set(ASCII_BORDER),
+        (false, Separator::Plain) => block.border_type(BorderType::Plain),
+        (false, Separator::Dotted) => block.border_set(border::Set {
+            horizontal_bottom: "┄",
+            ..border::PLAIN
+        }),
+        (false, Separator::Double) => block.border_type(BorderType::Double),
+        (false, Separator::Thick) => block.border_type(BorderType::Thick),
+    };
+    match &theme.separator_label {
+        Some(label) => block.title_bottom(Line::raw(format!(" {label} ")).centered()),
+        None => block,
+    }
+}
+
+/// Renders the outermost border with appropriate titles
+fn render_border(f: &mut Frame, area: Rect, theme: &Theme, title: &str) {
+    let instructions = Line::from(vec![
+        Span::styled(" [Q]", KEYBIND_STYLE),
+        Span::raw("uit "),
+        Span::styled("[A]", KEYBIND_STYLE),
+        Span::raw("dd "),
+        Span::styled("[D]", KEYBIND_STYLE),
+        Span::raw("elete "),
+        Span::styled("[C]", KEYBIND_STYLE),
+        Span::raw("omplete "),
+    ])
+    .centered();
+
+    f.render_widget(themed_block(theme, title).title_bottom(instructions), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::FixedClock;
+    use chrono::{TimeDelta, TimeZone};
+    use ratatui::widgets::Widget;
+    use std::rc::Rc;
+
+    fn app_with(todos: &[&'static str]) -> App {
+        App {
+            todo_list: todos.iter().map(|&t| (Status::Todo, t, "")).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn delete_selects_neighbor() {
+        let mut app = app_with(&["a", "b", "c", "d"]);
+        app.todo_list.state.select(Some(1));
+        app.delete_entry();
+        assert_eq!(app.todo_list.state.selected(), Some(1));
+        assert_eq!(app.todo_list.items[1].todo, "c");
+
+        app.todo_list.state.select(Some(2));
+        app.delete_entry();
+        assert_eq!(app.todo_list.state.selected(), Some(1));
+
+        let mut app = app_with(&["a", "b", "c"]);
+        app.config.delete_selection = DeleteSelection::Previous;
+        app.todo_list.state.select(Some(1));
+        app.delete_entry();
+        assert_eq!(app.todo_list.state.selected(), Some(0));
+        app.delete_entry();
+        assert_eq!(app.todo_list.state.selected(), Some(0));
+
+        let mut app = app_with(&["a"]);
+        app.todo_list.state.select(Some(0));
+        app.delete_entry();
+        assert_eq!(app.todo_list.state.selected(), None);
+    }
+
+    #[test]
+    fn restore_deleted_entry() {
+        let mut app = app_with(&["a", "b", "c"]);
+        app.todo_list.state.select(Some(1));
+        app.delete_entry();
+        assert_eq!(app.todo_list.items.len(), 2);
+
+        app.restore_deleted();
+        assert_eq!(app.todo_list.items[1].todo, "b");
+        assert_eq!(app.todo_list.state.selected(), Some(1));
+        assert!(app.last_deleted.is_none());
+
+        app.restore_deleted();
+        assert_eq!(app.todo_list.items.len(), 3);
+    }
+
+    #[test]
+    fn delete_in_normal_mode() {
+        let mut app = app_with(&["a"]);
+        app.todo_list.items[0].info = String::from("some notes");
+        app.todo_list.state.select(Some(0));
+        app.edit_entry();
+        app.switch_focus(Focus::Info);
+        app.edit_mode = Some(EditMode::Normal);
+        app.input.handle(InputRequest::GoToStart);
+        app.handle_edit_key_event(KeyCode::Char('x').into());
+        assert_eq!(app.todo_list.items[0].info, "ome notes");
+
+        // Another key cancels the pending operator
+        app.handle_edit_key_event(KeyCode::Char('d').into());
+        app.handle_edit_key_event(KeyCode::Char('y').into());
+        app.handle_edit_key_event(KeyCode::Char('d').into());
+        assert_eq!(app.todo_list.items[0].info, "ome notes");
+        app.handle_edit_key_event(KeyCode::Char('d').into());
+        assert_eq!(app.todo_list.items[0].info, "");
+        assert_eq!(app.input.value(), "");
+
+        // Nothing to delete
+        app.config.feedback = Feedback::Flash;
+        app.handle_edit_key_event(KeyCode::Char('x').into());
+        assert!(app.feedback);
+    }
+
+    #[test]
+    fn motions_in_normal_mode() {
+        let mut app = app_with(&["a"]);
+        app.todo_list.items[0].info = String::from("first line\nsecond  line");
+        app.todo_list.state.select(Some(0));
+        app.edit_entry();
+        app.switch_focus(Focus::Info);
+        app.edit_mode = Some(EditMode::Normal);
+        let mut press = |c| {
+            app.handle_edit_key_event(KeyCode::Char(c).into());
+            app.input.cursor()
+        };
+        assert_eq!(press('0'), 11);
+        assert_eq!(press('$'), 22);
+        assert_eq!(press('b'), 19);
+        assert_eq!(press('b'), 11);
+        assert_eq!(press('b'), 6);
+        assert_eq!(press('$'), 9);
+        assert_eq!(press('w'), 11);
+        assert_eq!(press('0'), 11);
+        assert_eq!(press('w'), 19);
+        assert_eq!(press('w'), 23);
+        assert_eq!(app.todo_list.items[0].info, "first line\nsecond  line");
+
+        // The cursor is drawn on the jumped to character, even across wrapped lines
+        let field = InputField::new(app.input.value(), Wrap::Word);
+        let area = Rect::new(0, 0, 6, 5);
+        app.input.handle(InputRequest::SetCursor(11));
+        app.handle_edit_key_event(KeyCode::Char('w').into());
+        assert_eq!(field.get_cursor_at(area, app.input.cursor()), (2, 3));
+        app.handle_edit_key_event(KeyCode::Char('b').into());
+        assert_eq!(field.get_cursor_at(area, app.input.cursor()), (0, 2));
+    }
+
+    #[test]
+    fn yank_and_paste_field() {
+        let mut app = app_with(&["a", "b"]);
+        app.todo_list.items[0].info = String::from("some notes");
+        app.todo_list.state.select(Some(0));
+        app.edit_entry();
+        app.switch_focus(Focus::Info);
+        app.handle_edit_key_event(KeyCode::Char('y').into());
+        app.handle_edit_key_event(KeyCode::Char('y').into());
+        app.switch_view(View::List);
+
+        app.todo_list.state.select(Some(1));
+        app.edit_entry();
+        app.switch_focus(Focus::Info);
+        app.handle_edit_key_event(KeyCode::Char('p').into());
+        assert_eq!(app.todo_list.items[1].info, "some notes");
+        assert_eq!(app.todo_list.items[0].info, "some notes");
+    }
+
+    #[test]
+    fn modal_size_clamping() {
+        let bounds = ModalSize {
+            min_width: 40,
+            min_height: 15,
+            max_width: 80,
+            max_height: 30,
+        };
+        let area = |width, height| Rect::new(0, 0, width, height);
+        assert_eq!(modal_size(area(30, 40), &bounds), None);
+        assert_eq!(modal_size(area(100, 10), &bounds), None);
+        assert_eq!(modal_size(area(40, 15), &bounds), Some((40, 15)));
+        assert_eq!(modal_size(area(60, 20), &bounds), Some((56, 18)));
+        assert_eq!(modal_size(area(200, 100), &bounds), Some((80, 30)));
+    }
+
+    #[test]
+    fn cycle_in_progress() {
+        let mut app = app_with(&["a", "b"]);
+        app.todo_list.state.select(Some(0));
+        app.handle_list_key_event(KeyCode::Char('x').into());
+        let item = &app.todo_list.items[0];
+        assert_eq!(item.status, Status::InProgress);
+        assert_eq!(item.completed_at, None);
+        assert_eq!(list_line(item, &app.config).to_string(), "· ◐ a");
+        let style = list_line(item, &app.config).spans[1].style;
+        assert_eq!(style.fg, Some(app.config.theme.palette().in_progress));
+
+        // In progress items still count as incomplete
+        app.filter_status(StatusFilter::Todo);
+        assert_eq!(app.visible(), vec![0, 1]);
+        app.filter_status(StatusFilter::All);
+
+        app.handle_list_key_event(KeyCode::Char('x').into());
+        assert_eq!(app.todo_list.items[0].status, Status::Completed);
+        assert!(app.todo_list.items[0].completed_at.is_some());
+        app.handle_list_key_event(KeyCode::Char('x').into());
+        assert_eq!(app.todo_list.items[0].status, Status::Todo);
+
+        // Toggling completes an item in progress, undoing puts it back
+        app.handle_list_key_event(KeyCode::Char('x').into());
+        app.handle_list_key_event(KeyCode::Char('c').into());
+        assert_eq!(app.todo_list.items[0].status, Status::Completed);
+        app.undo();
+        assert_eq!(app.todo_list.items[0].status, Status::InProgress);
+    }
+
+    #[test]
+    fn auto_save_after_idle() {
+        let start = Instant::now();
+        let idle = Some(Duration::from_secs(30));
+        let later = |secs| start + Duration::from_secs(secs);
+        assert!(!should_auto_save(Some(start), later(10), idle));
+        assert!(should_auto_save(Some(start), later(30), idle));
+        assert!(!should_auto_save(None, later(60), idle));
+        assert!(!should_auto_save(Some(start), later(60), None));
+    }
+
+    #[test]
+    fn confirm_completing_parent() {
+        let mut parent = TodoItem::new(Status::Todo, "parent", "");
+        let mut child = TodoItem::new(Status::Completed, "a", "");
+        child.subtasks = vec![TodoItem::new(Status::Todo, "a1", "")];
+        parent.subtasks = vec![child, TodoItem::new(Status::Todo, "b", "")];
+        assert_eq!(parent.incomplete_subtasks(), 2);
+        assert_eq!(parent.subtasks[1].incomplete_subtasks(), 0);
+
+        let mut app = App::default();
+        app.todo_list.items.push(parent);
+        app.todo_list.state.select(Some(0));
+        app.config.confirm_incomplete_subtasks = true;
+
+        // Anything but y cancels
+        app.handle_list_key_event(KeyCode::Char('c').into());
+        assert_eq!(
+            app.message.as_deref(),
+            Some("2 subtasks are incomplete, complete anyway? [y/n]")
+        );
+        app.handle_list_key_event(KeyCode::Char('n').into());
+        assert!(matches!(app.todo_list.items[0].status, Status::Todo));
+
+        app.config.complete_subtasks = true;
+        app.handle_list_key_event(KeyCode::Char('c').into());
+        app.handle_list_key_event(KeyCode::Char('y').into());
+        assert!(matches!(app.todo_list.items[0].status, Status::Completed));
+        assert_eq!(app.todo_list.items[0].incomplete_subtasks(), 0);
+
+        // Reopening never asks
+        app.handle_list_key_event(KeyCode::Char('c').into());
+        assert!(matches!(app.todo_list.items[0].status, Status::Todo));
+    }
+
+    #[test]
+    fn build_todo_item() {
+        let due = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let item = TodoItem::builder()
+            .id(7)
+            .todo("taxes")
+            .info("forms")
+            .status(Status::Completed)
+            .tags(["home", "money"])
+            .due(due)
+            .subtasks(vec![TodoItem::new(Status::Todo, "receipts", "")])
+            .priority(Priority::High)
+            .build();
+        assert_eq!(item.id, 7);
+        assert_eq!((item.todo.as_str(), item.info.as_str()), ("taxes", "forms"));
+        assert_eq!(item.status, Status::Completed);
+        assert_eq!(item.tags, vec!["home", "money"]);
+        assert_eq!(item.due, Some(due));
+        assert_eq!(item.subtasks[0].todo, "receipts");
+        assert_eq!(item.priority, Priority::High);
+
+        let item = TodoItem::builder().todo("bare").build();
+        assert_eq!(item.status, Status::Todo);
+        assert_eq!(item.priority, Priority::Medium);
+        assert!(item.info.is_empty() && item.tags.is_empty() && item.due.is_none());
+    }
+
+    #[test]
+    fn highlight_saved_queries() {
+        let queries = vec![
+            SavedQuery {
+                tag: Some(String::from("work")),
+                status: Some(QueryStatus::Todo),
+                color: Color::Red,
+                ..Default::default()
+            },
+            SavedQuery {
+                text: Some(String::from("MILK")),
+                color: Color::Blue,
+                ..Default::default()
+            },
+        ];
+        let work = TodoItem::builder().todo("report").tags(["work"]).build();
+        let done = TodoItem::builder()
+            .todo("slides")
+            .tags(["work"])
+            .status(Status::Completed)
+            .build();
+        let milk = TodoItem::builder().todo("buy milk").tags(["work"]).build();
+        let other = TodoItem::new(Status::Todo, "walk", "");
+
+        let red = Some(Style::new().fg(Color::Red));
+        assert_eq!(saved_query_style(&work, &queries), red);
+        assert_eq!(saved_query_style(&done, &queries), None);
+        // The first matching query wins
+        assert_eq!(saved_query_style(&milk, &queries), red);
+        assert_eq!(
+            saved_query_style(&milk, &queries[1..]),
+            Some(Style::new().fg(Color::Blue))
+        );
+        assert_eq!(saved_query_style(&other, &queries), None);
+
+        let started = SavedQuery {
+            status: Some(QueryStatus::InProgress),
+            color: Color::Green,
+            ..Default::default()
+        };
+        let mut doing = TodoItem::new(Status::InProgress, "draft", "");
+        assert!(matches_query(&doing, &started));
+        doing.status = Status::Todo;
+        assert!(!matches_query(&doing, &started));
+    }
+
+    #[test]
+    fn split_pasted_block() {
+        let (title, info) = split_paste("\n  Plan trip \n\nbook flights\n  pack\n");
+        assert_eq!(title, "Plan trip");
+        assert_eq!(info, "book flights\npack");
+        assert_eq!(
+            split_paste("one line"),
+            (String::from("one line"), String::new())
+        );
+
+        let mut app = app_with(&["a", "b"]);
+        app.todo_list.state.select(Some(0));
+        app.edit_entry();
+        app.edit_mode = Some(EditMode::Insert);
+        app.input = Input::default();
+        app.handle_paste("Plan trip\r\nbook flights\r\npack");
+        assert_eq!(app.todo_list.items[0].todo, "Plan trip");
+        assert_eq!(app.todo_list.items[0].info, "book flights\npack");
+
+        app.config.multiline_paste = MultilinePaste::Items;
+        app.input = Input::default();
+        app.handle_paste("x\ny\nz");
+        let titles: Vec<&str> = app
+            .todo_list
+            .items
+            .iter()
+            .map(|i| i.todo.as_str())
+            .collect();
+        assert_eq!(titles, vec!["x", "y", "z", "b"]);
+    }
+
+    #[test]
+    fn page_long_info() {
+        let path = std::env::temp_dir().join("todo_pager.txt");
+        _ = std::fs::remove_file(&path);
+        let info = "line 1\nline 2\n";
+        pipe_to(&format!("dd status=none of={}", path.display()), info).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), info);
+        assert!(pipe_to("todui-missing-pager", info).is_err());
+
+        let mut app = app_with(&["a"]);
+        app.todo_list.state.select(Some(0));
+        app.handle_list_key_event(KeyCode::Char('P').into());
+        assert!(matches!(app.external, Some(External::Pager)));
+
+        // Without a pager the details are shown in the app
+        app.external = None;
+        app.finish_paging(Ok(()));
+        assert!(matches!(app.view, View::List));
+        app.finish_paging(pipe_to("todui-missing-pager", info));
+        assert!(matches!(app.view, View::Detail));
+    }
+
+    #[test]
+    fn capitalize_todo_on_save() {
+        let mut app = app_with(&["a"]);
+        app.todo_list.state.select(Some(0));
+        app.edit_entry();
+        app.input = Input::new(String::from("buy milk"));
+        app.save_input();
+        assert_eq!(app.todo_list.items[0].todo, "buy milk");
+
+        app.config.capitalize_todos = true;
+        app.save_input();
+        assert_eq!(app.todo_list.items[0].todo, "Buy milk");
+        assert_eq!(capitalize("éclair"), "Éclair");
+        assert_eq!(capitalize("ßtraße"), "SStraße");
+        assert_eq!(capitalize(""), "");
+    }
+
+    #[test]
+    fn subtask_rollup() {
+        let mut parent = TodoItem::new(Status::Todo, "parent", "");
+        assert_eq!(parent.progress(), None);
+        parent.subtasks = vec![
+            TodoItem::new(Status::Completed, "a", ""),
+            TodoItem::new(Status::Todo, "b", ""),
+        ];
+        assert_eq!(parent.progress(), Some((1, 2)));
+
+        let mut app = App::default();
+        app.config.auto_complete_parents = true;
+        app.todo_list.items.push(parent);
+        app.apply_rollups();
+        assert!(matches!(app.todo_list.items[0].status, Status::Todo));
+
+        app.todo_list.items[0].subtasks[1].status = Status::Completed;
+        app.apply_rollups();
+        assert_eq!(app.todo_list.items[0].progress(), Some((2, 2)));
+        assert!(matches!(app.todo_list.items[0].status, Status::Completed));
+    }
+
+    #[test]
+    fn edit_item_as_json() {
+        let item = TodoItem::new(Status::Todo, "old", "info");
+        let edited = data::edit_as_json(&item, |path| {
+            let json = std::fs::read_to_string(path)?;
+            std::fs::write(
+                path,
+                json.replace("old", "new").replace("Todo", "Completed"),
+            )
+        });
+        let edited = edited.unwrap();
+        assert_eq!(edited.todo, "new");
+        assert_eq!(edited.info, "info");
+        assert!(matches!(edited.status, Status::Completed));
+
+        let broken = data::edit_as_json(&item, |path| std::fs::write(path, "{ oops"));
+        assert!(matches!(broken, Err(data::Error::Serde(_))));
+        assert_eq!(item.todo, "old");
+    }
+
+    #[test]
+    fn themed_block_border() {
+        let render = |theme: &Theme| {
+            let area = Rect::new(0, 0, 10, 3);
+            let mut buf = ratatui::buffer::Buffer::empty(area);
+            themed_block(theme, &theme.list_title).render(area, &mut buf);
+            buf
+        };
+        let buf = render(&Theme::default());
+        assert_eq!(buf[(0, 0)].symbol(), "╭");
+        assert_eq!(buf[(3, 0)].symbol(), "T");
+
+        let theme = Theme {
+            border: config::Border::Double,
+            list_title: String::from("Work"),
+            ..Default::default()
+        };
+        let buf = render(&theme);
+        assert_eq!(buf[(0, 0)].symbol(), "╔");
+        assert_eq!(buf[(3, 0)].symbol(), "W");
+    }
+
+    #[test]
+    fn commit_search_selects_top_match() {
+        let mut app = app_with(&["buy milk", "call mom", "milk", "walk dog"]);
+        app.todo_list.items[1].info = String::from("ask about milk");
+        app.handle_list_key_event(KeyCode::Char('/').into());
+        for c in "milk".chars() {
+            app.handle_list_key_event(KeyCode::Char(c).into());
+        }
+        assert_eq!(app.visible(), vec![2, 0, 1]);
+        assert_eq!(app.selected_index(), Some(2));
+
+        app.handle_list_key_event(KeyCode::Enter.into());
+        assert!(!app.searching);
+        assert_eq!(app.visible(), vec![0, 1, 2]);
+        assert_eq!(app.selected_index(), Some(2));
+        assert!(matches!(app.view, View::List));
+
+        app.config.search_commit = SearchCommit::Open;
+        app.handle_list_key_event(KeyCode::Char('/').into());
+        app.handle_list_key_event(KeyCode::Char('b').into());
+        app.handle_list_key_event(KeyCode::Enter.into());
+        assert_eq!(app.selected_index(), Some(0));
+        assert!(matches!(app.view, View::Detail));
+    }
+
+    #[test]
+    fn noop_sets_feedback() {
+        let mut app = app_with(&["a", "b"]);
+        app.handle_list_key_event(KeyCode::Char('d').into());
+        assert!(!app.feedback);
+
+        app.config.feedback = Feedback::Flash;
+        app.handle_list_key_event(KeyCode::Char('d').into());
+        assert!(app.feedback);
+
+        app.feedback = false;
+        app.handle_list_key_event(KeyCode::Char('j').into());
+        app.handle_list_key_event(KeyCode::Char('j').into());
+        assert!(!app.feedback);
+        app.handle_list_key_event(KeyCode::Char('j').into());
+        assert!(app.feedback);
+        assert_eq!(app.todo_list.state.selected(), Some(1));
+    }
+
+    #[test]
+    fn tag_display_order() {
+        let tags = ["work", "errand", "urgent", "home"].map(String::from);
+        let priority = ["urgent", "home"].map(String::from);
+        assert_eq!(
+            display_tags(&tags, TagOrder::Insertion, &priority),
+            ["work", "errand", "urgent", "home"]
+        );
+        assert_eq!(
+            display_tags(&tags, TagOrder::Alphabetical, &priority),
+            ["errand", "home", "urgent", "work"]
+        );
+        assert_eq!(
+            display_tags(&tags, TagOrder::Priority, &priority),
+            ["urgent", "home", "errand", "work"]
+        );
+        assert_eq!(tags[0], "work");
+    }
+
+    #[test]
+    fn merge_with_item_below() {
+        let mut app = app_with(&["a", "buy", "milk", "d"]);
+        for (id, item) in app.todo_list.items.iter_mut().enumerate() {
+            item.id = id as u64 + 1;
+        }
+        app.todo_list.items[1].info = String::from("at the store");
+        app.todo_list.items[1].tags = vec![String::from("home")];
+        app.todo_list.items[2].info = String::from("2 litres");
+        app.todo_list.items[2].tags = vec![String::from("home"), String::from("food")];
+        app.todo_list.items[2].set_status(Status::Completed, Local::now());
+        app.todo_list.state.select(Some(1));
+        app.handle_list_key_event(KeyCode::Char('M').into());
+
+        assert_eq!(app.todo_list.items.len(), 3);
+        let merged = &app.todo_list.items[1];
+        assert_eq!(merged.todo, "buy milk");
+        assert_eq!(merged.info, "at the store\n2 litres");
+        assert_eq!(merged.tags, ["home", "food"]);
+        assert!(matches!(merged.status, Status::Todo));
+        assert_eq!(app.todo_list.items[2].todo, "d");
+        assert_eq!(app.selected_index(), Some(1));
+
+        // Undoing brings back both items as they were
+        app.handle_list_key_event(KeyCode::Char('u').into());
+        let todos: Vec<&str> = app
+            .todo_list
+            .items
+            .iter()
+            .map(|item| item.todo.as_str())
+            .collect();
+        assert_eq!(todos, vec!["a", "buy", "milk", "d"]);
+        assert_eq!(app.todo_list.items[1].info, "at the store");
+        assert_eq!(app.todo_list.items[1].tags, ["home"]);
+        assert!(matches!(app.todo_list.items[2].status, Status::Completed));
+        assert_eq!(app.selected_index(), Some(1));
+    }
+
+    #[test]
+    fn trim_whitespace_on_save() {
+        let mut app = app_with(&["a"]);
+        app.todo_list.state.select(Some(0));
+        app.edit_entry();
+        app.input = Input::new(String::from("  buy  milk \t"));
+        app.switch_focus(Focus::Info);
+        assert_eq!(app.todo_list.items[0].todo, "buy  milk");
+
+        app.input = Input::new(String::from(" - eggs\n - bread\n "));
+        app.save_input();
+        assert_eq!(app.todo_list.items[0].info, "- eggs\n - bread\n");
+
+        app.config.trim_whitespace = false;
+        app.input = Input::new(String::from(" notes "));
+        app.save_input();
+        assert_eq!(app.todo_list.items[0].info, " notes ");
+    }
+
+    #[test]
+    fn tab_cycles_focus() {
+        let mut app = app_with(&["a"]);
+        app.todo_list.state.select(Some(0));
+        app.edit_entry();
+        app.edit_mode = Some(EditMode::Insert);
+        app.handle_edit_key_event(KeyCode::Char('b').into());
+        app.handle_edit_key_event(KeyCode::Tab.into());
+        assert_eq!(app.todo_list.items[0].todo, "ab");
+        assert!(app.focus == Some(Focus::Tags));
+
+        app.handle_edit_key_event(KeyCode::Tab.into());
+        assert!(app.focus == Some(Focus::Due));
+        app.handle_edit_key_event(KeyCode::Tab.into());
+        app.handle_edit_key_event(KeyCode::Tab.into());
+        assert!(app.focus == Some(Focus::Todo));
+        assert_eq!(app.input.value(), "ab");
+
+        app.handle_edit_key_event(KeyCode::BackTab.into());
+        assert!(app.focus == Some(Focus::Info));
+        assert!(matches!(app.edit_mode, Some(EditMode::Insert)));
+    }
+
+    #[test]
+    fn list_title_badge() {
+        let mut app = app_with(&["a", "b", "c"]);
+        assert_eq!(app.list_title(), "TODO");
+
+        app.config.count_badge = true;
+        assert_eq!(app.list_title(), "TODO (3)");
+        app.todo_list.items[1].set_status(Status::Completed, Local::now());
+        assert_eq!(app.list_title(), "TODO (2)");
+        for item in app.todo_list.items.iter_mut() {
+            item.set_status(Status::Completed, Local::now());
+        }
+        assert_eq!(app.list_title(), "TODO (✓)");
+    }
+
+    #[test]
+    fn repeat_last_action() {
+        let mut app = app_with(&["a", "b", "c"]);
+        app.todo_list.state.select(Some(0));
+        app.handle_list_key_event(KeyCode::Char('c').into());
+        app.handle_list_key_event(KeyCode::Char('j').into());
+        app.handle_list_key_event(KeyCode::Char('.').into());
+        assert!(matches!(app.todo_list.items[1].status, Status::Completed));
+        assert!(matches!(app.todo_list.items[2].status, Status::Todo));
+
+        // Navigation is not recorded, so '.' still toggles
+        app.handle_list_key_event(KeyCode::Char('.').into());
+        assert!(matches!(app.todo_list.items[1].status, Status::Todo));
+
+        // An action that changed nothing is neither recorded nor saved
+        app.last_change = None;
+        app.todo_list.state.select(None);
+        app.handle_list_key_event(KeyCode::Char('!').into());
+        assert!(app.last_change.is_none());
+        assert!(matches!(app.last_action, Some(Action::ToggleStatus)));
+    }
+
+    #[test]
+    fn expanded_item_lines() {
+        let lines = expanded_lines("A wrap occurs\nnext", 9, WrapMode::Word);
+        let lines: Vec<String> = lines.iter().map(Line::to_string).collect();
+        assert_eq!(lines, vec!["  A wrap ", "  occurs", "  next"]);
+        assert!(expanded_lines("", 9, WrapMode::Word).is_empty());
+
+        let item = TodoItem::new(Status::Todo, "title", "body");
+        let config = Config::default();
+        assert_eq!(expanded_item(&item, &config, 20).height(), 2);
+        assert_eq!(list_item(&item, &config).height(), 1);
+    }
+
+    #[test]
+    fn refuse_adding_past_max() {
+        let mut app = app_with(&["a", "b"]);
+        app.config.max_todos = Some(2);
+        app.add_entry();
+        assert_eq!(app.todo_list.items.len(), 2);
+        assert!(matches!(app.view, View::List));
+        assert_eq!(app.message.as_deref(), Some(data::FULL_MESSAGE));
+
+        app.config.max_todos = Some(3);
+        app.add_entry();
+        assert_eq!(app.todo_list.items.len(), 3);
+    }
+
+    #[test]
+    fn exit_summary_lines() {
+        let mut app = app_with(&["write", "read"]);
+        assert_eq!(app.exit_summary(), None);
+
+        app.config.print_on_exit = true;
+        app.todo_list.items[0].tags = vec![String::from("work")];
+        app.todo_list.items[1].set_status(Status::Completed, Local::now());
+        let sub = TodoItem::new(Status::Todo, "chapter", "");
+        app.todo_list.items[1].subtasks.push(sub);
+        assert_eq!(
+            app.exit_summary().as_deref(),
+            Some("☐ write #work\n✓ read (0/1)\n")
         );
+    }
 
-        // Handle the focused area
-        let input_field = InputField::new(self.input.value(), Wrap::Word);
-        match focus {
-            Focus::Todo => f.render_widget(&input_field, todo_area),
-            Focus::Info => f.render_widget(&input_field, info_area),
+    #[test]
+    fn yank_visible_list() {
+        let mut app = app_with(&["write", "read", "sleep"]);
+        app.todo_list.items[1].set_status(Status::Completed, Local::now());
+        app.todo_list.items[2].info = String::from("early");
+        assert_eq!(
+            app.visible_markdown(),
+            "- [ ] write\n- [x] read\n- [ ] sleep\n  early\n"
+        );
+        app.filter_status(StatusFilter::Todo);
+        let markdown = app.visible_markdown();
+        assert_eq!(markdown, "- [ ] write\n- [ ] sleep\n  early\n");
+
+        // Without a clipboard it is printed on exit
+        app.finish_yank_list(Ok(()), markdown.clone());
+        assert_eq!(app.exit_summary(), None);
+        let no_clipboard = Err(io::Error::other("No clipboard tool found."));
+        app.finish_yank_list(no_clipboard, markdown.clone());
+        assert_eq!(app.exit_summary(), Some(markdown));
+    }
+
+    #[test]
+    fn edit_view_consumes_list_keys() {
+        let mut app = app_with(&["a", "b"]);
+        app.config.feedback = Feedback::Flash;
+        app.todo_list.state.select(Some(0));
+        app.edit_entry();
+        for c in ['u', 'J', 'a', 'c', 'o', '>', ' '] {
+            app.feedback = false;
+            app.handle_key_event(KeyCode::Char(c).into());
+            assert!(app.feedback);
         }
+        assert!(matches!(app.view, View::Edit));
+        assert_eq!(app.todo_list.items.len(), 2);
+        assert!(matches!(app.todo_list.items[0].status, Status::Todo));
+        assert!(app.marked.is_empty());
+    }
 
-        // Handle the non focused areas
-        if !matches!(focus, Focus::Todo) {
-            let err = "Expected a selected ListItem in Edit View.";
-            let index = self.editing_index.expect(err);
-            let selected_item = self.todo_list.items.get(index).expect(err);
-            let text = selected_item.todo.clone();
-            f.render_widget(&InputField::new(text, Wrap::Word), todo_area);
+    #[test]
+    fn due_date_field() {
+        let mut app = app_with(&["rent"]);
+        app.todo_list.state.select(Some(0));
+        app.edit_entry();
+        app.switch_focus(Focus::Due);
+        assert_eq!(app.input.value(), "");
+        app.input = Input::new(String::from("2024-06-01"));
+        app.switch_focus(Focus::Info);
+        let due = NaiveDate::from_ymd_opt(2024, 6, 1);
+        assert_eq!(app.todo_list.items[0].due, due);
+
+        // Invalid dates are reported and keep the previous date
+        app.switch_focus(Focus::Due);
+        assert_eq!(app.input.value(), "2024-06-01");
+        app.input = Input::new(String::from("June 1st"));
+        app.switch_focus(Focus::Info);
+        assert_eq!(app.todo_list.items[0].due, due);
+        assert!(
+            app.message
+                .as_deref()
+                .unwrap()
+                .starts_with("Invalid due date")
+        );
+
+        // Clearing the field clears the date
+        app.switch_focus(Focus::Due);
+        app.input = Input::default();
+        app.switch_focus(Focus::Todo);
+        assert_eq!(app.todo_list.items[0].due, None);
+
+        assert_eq!(due_hint("2024-06-01").to_string(), "Saturday, June 1, 2024");
+        assert_eq!(
+            due_hint("2024-13-01").to_string(),
+            "not a date (YYYY-MM-DD)"
+        );
+        assert_eq!(due_hint(" ").to_string(), "");
+    }
+
+    #[test]
+    fn overdue_items() {
+        let today = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let mut item = TodoItem::new(Status::Todo, "rent", "");
+        assert!(!is_overdue(&item, today));
+        item.due = today.pred_opt();
+        assert!(is_overdue(&item, today));
+        assert_eq!(
+            list_line(&item, &Config::default()).to_string(),
+            "· ☐ rent  due 2024-05-31"
+        );
+        item.set_status(Status::Completed, Local::now());
+        assert!(!is_overdue(&item, today));
+        item.due = Some(today);
+        item.set_status(Status::Todo, Local::now());
+        assert!(!is_overdue(&item, today));
+    }
+
+    #[test]
+    fn undo_changes() {
+        let mut app = app_with(&["a", "b", "c"]);
+        for (id, item) in app.todo_list.items.iter_mut().enumerate() {
+            item.id = id as u64 + 1;
         }
+        let todos = |app: &App| -> Vec<String> {
+            app.todo_list
+                .items
+                .iter()
+                .map(|item| item.todo.clone())
+                .collect()
+        };
+        app.config.confirm_delete = false;
+        app.todo_list.state.select(Some(1));
+        app.handle_list_key_event(KeyCode::Char('c').into());
+        app.handle_list_key_event(KeyCode::Char('J').into());
+        app.handle_list_key_event(KeyCode::Char('d').into());
+        assert_eq!(todos(&app), ["a", "c"]);
 
-        if !matches!(focus, Focus::Info) {
-            let err = "Expected a selected ListItem in Edit View.";
-            let index = self.editing_index.expect(err);
-            let selected_item = self.todo_list.items.get(index).expect(err);
-            let text = selected_item.info.clone();
-            f.render_widget(&InputField::new(text, Wrap::Word), info_area);
+        // A deleted item comes back selected
+        app.handle_list_key_event(KeyCode::Char('k').into());
+        app.handle_list_key_event(KeyCode::Char('u').into());
+        assert_eq!(todos(&app), ["a", "c", "b"]);
+        assert_eq!(app.selected_index(), Some(2));
+        assert!(app.last_deleted.is_none());
+        app.handle_list_key_event(KeyCode::Char('u').into());
+        assert_eq!(todos(&app), ["a", "b", "c"]);
+        assert_eq!(app.selected_index(), Some(1));
+        app.handle_list_key_event(KeyCode::Char('u').into());
+        assert!(matches!(app.todo_list.items[1].status, Status::Todo));
+        app.handle_list_key_event(KeyCode::Char('u').into());
+        assert_eq!(app.message.as_deref(), Some("Nothing to undo"));
+
+        // Adding is undone by removing the item
+        app.add_entry();
+        app.switch_view(View::List);
+        assert_eq!(app.todo_list.items.len(), 4);
+        app.handle_list_key_event(KeyCode::Char('u').into());
+        assert_eq!(todos(&app), ["a", "b", "c"]);
+
+        // The history is bounded
+        for _ in 0..UNDO_LIMIT + 10 {
+            app.handle_list_key_event(KeyCode::Char('!').into());
         }
+        assert_eq!(app.undo_stack.len(), UNDO_LIMIT);
+    }
 
-        // Footer area
-        let editor_mode = match self.edit_mode.as_ref().expect("Expected an editor mode.") {
-            EditMode::Normal => " NORMAL Mode ",
-            EditMode::Insert => " INSERT Mode ",
+    #[test]
+    fn dismiss_reload_prompt() {
+        let dir = std::env::temp_dir().join("todo_dismiss_reload");
+        let data_path = dir.join("todos.json");
+        let file = data::TodoFile::new(vec![TodoItem::new(Status::Todo, "a", "")]);
+        data::write_todos(&file, &data_path, JsonFormat::Compact).unwrap();
+        let mut app = App {
+            data_path: data_path.clone(),
+            ..Default::default()
         };
-        f.render_widget(Paragraph::new(editor_mode), footer_area);
+        app.load();
 
-        // Render cursor
-        match self.focus.clone().expect("Expected a focus.") {
-            Focus::Todo => render_cursor(
-                f,
-                input_field.get_cursor_at(todo_area, self.input.value().len()),
-            ),
-            Focus::Info => render_cursor(
-                f,
-                input_field.get_cursor_at(info_area, self.input.value().len()),
-            ),
+        let file = data::TodoFile::new(vec![TodoItem::new(Status::Todo, "changed", "")]);
+        data::write_todos(&file, &data_path, JsonFormat::Compact).unwrap();
+        app.on_tick(Instant::now());
+        assert!(app.reload_prompt);
+        app.handle_key_event(KeyCode::Esc.into());
+        assert!(!app.reload_prompt);
+        assert_eq!(app.todo_list.items[0].todo, "a");
+        app.on_tick(Instant::now());
+        assert!(!app.reload_prompt);
+
+        // Changing the todos asks again
+        app.mark_changed();
+        app.on_tick(Instant::now());
+        assert!(app.reload_prompt);
+        _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn reload_clears_prompts() {
+        let dir = std::env::temp_dir().join("todo_reload_prompts");
+        let data_path = dir.join("todos.json");
+        let file = data::TodoFile::new(vec![TodoItem::new(Status::Todo, "a", "")]);
+        data::write_todos(&file, &data_path, JsonFormat::Compact).unwrap();
+        let mut app = App {
+            data_path,
+            ..app_with(&["a", "b", "c"])
+        };
+        app.confirming_delete = Some(2);
+        app.confirming_completion = Some(2);
+        app.time_prompt = Some((2, Input::default()));
+        app.demoting = Some(2);
+        app.palette = Some(Palette::default());
+        app.reload();
+        assert!(app.confirming_delete.is_none());
+        assert!(app.confirming_completion.is_none());
+        assert!(app.time_prompt.is_none());
+        assert!(app.demoting.is_none());
+        assert!(app.palette.is_none());
+        _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn confirm_before_delete() {
+        let mut app = app_with(&["a", "b", "c"]);
+        for (id, item) in app.todo_list.items.iter_mut().enumerate() {
+            item.id = id as u64 + 1;
         }
+        app.todo_list.state.select(Some(1));
+        app.handle_list_key_event(KeyCode::Char('d').into());
+        assert_eq!(app.confirming_delete, Some(2));
+        // Other keys are ignored until answered
+        app.handle_list_key_event(KeyCode::Char('j').into());
+        app.handle_list_key_event(KeyCode::Char('n').into());
+        assert!(app.confirming_delete.is_none());
+        assert_eq!(app.todo_list.items.len(), 3);
+        assert_eq!(app.selected_index(), Some(1));
+
+        app.handle_list_key_event(KeyCode::Char('d').into());
+        app.handle_list_key_event(KeyCode::Esc.into());
+        assert_eq!(app.todo_list.items.len(), 3);
+        app.handle_list_key_event(KeyCode::Char('d').into());
+        app.handle_list_key_event(KeyCode::Char('y').into());
+        assert_eq!(app.todo_list.items[1].todo, "c");
+
+        // The asked about item is deleted, even if it moved in the meantime
+        app.handle_list_key_event(KeyCode::Char('d').into());
+        app.todo_list.items.swap(0, 1);
+        app.handle_list_key_event(KeyCode::Char('y').into());
+        assert_eq!(app.todo_list.items.len(), 1);
+        assert_eq!(app.todo_list.items[0].todo, "a");
+        // Nothing is deleted when it is gone
+        app.confirming_delete = Some(3);
+        app.handle_list_key_event(KeyCode::Char('y').into());
+        assert_eq!(app.todo_list.items.len(), 1);
+        app.todo_list
+            .items
+            .push(TodoItem::new(Status::Todo, "d", ""));
+        app.todo_list.items[1].id = 4;
+        app.todo_list.state.select(Some(1));
+
+        app.config.confirm_delete = false;
+        app.handle_list_key_event(KeyCode::Char('d').into());
+        assert_eq!(app.todo_list.items.len(), 1);
     }
-}
 
-impl TodoItem {
-    fn new(status: Status, todo: &str, info: &str) -> Self {
-        Self {
-            status,
-            todo: String::from(todo),
-            info: String::from(info),
+    #[test]
+    fn move_entries() {
+        let mut app = app_with(&["a", "b", "c"]);
+        let todos = |app: &App| -> Vec<String> {
+            app.todo_list
+                .items
+                .iter()
+                .map(|item| item.todo.clone())
+                .collect()
+        };
+        app.todo_list.state.select(Some(0));
+        app.handle_list_key_event(KeyCode::Char('K').into());
+        assert_eq!(todos(&app), ["a", "b", "c"]);
+        app.handle_list_key_event(KeyCode::Char('J').into());
+        app.handle_list_key_event(KeyCode::Char('J').into());
+        assert_eq!(todos(&app), ["b", "c", "a"]);
+        assert_eq!(app.selected_index(), Some(2));
+        app.handle_list_key_event(KeyCode::Char('J').into());
+        assert_eq!(todos(&app), ["b", "c", "a"]);
+        app.handle_list_key_event(KeyCode::Char('K').into());
+        assert_eq!(todos(&app), ["b", "a", "c"]);
+        assert_eq!(app.todo_list.state.selected(), Some(1));
+
+        // Hidden items are skipped over
+        app.todo_list.items[1].set_status(Status::Completed, Local::now());
+        app.filter_status(StatusFilter::Todo);
+        app.todo_list.state.select(Some(1));
+        app.handle_list_key_event(KeyCode::Char('K').into());
+        assert_eq!(todos(&app), ["c", "a", "b"]);
+        assert_eq!(app.selected_index(), Some(0));
+
+        // Sorted lists can't be rearranged
+        app.filter_status(StatusFilter::All);
+        app.cycle_sort();
+        app.handle_list_key_event(KeyCode::Char('J').into());
+        assert_eq!(todos(&app), ["c", "a", "b"]);
+    }
+
+    #[test]
+    fn priority_cycle_and_sort() {
+        let mut app = app_with(&["low", "done", "high", "medium"]);
+        app.todo_list.items[0].priority = Priority::Low;
+        app.todo_list.items[1].priority = Priority::High;
+        app.todo_list.items[1].set_status(Status::Completed, Local::now());
+        app.todo_list.state.select(Some(2));
+        app.handle_list_key_event(KeyCode::Char('!').into());
+        assert_eq!(app.todo_list.items[2].priority, Priority::High);
+        assert_eq!(
+            list_line(&app.todo_list.items[2], &app.config).to_string(),
+            "! ☐ high"
+        );
+
+        // Completed items stay at the bottom, the selection follows its item
+        app.handle_list_key_event(KeyCode::Char('S').into());
+        assert!(app.sort == Sort::Priority);
+        assert_eq!(app.visible(), vec![2, 3, 0, 1]);
+        assert_eq!(app.selected_index(), Some(2));
+        app.handle_list_key_event(KeyCode::Char('j').into());
+        app.handle_list_key_event(KeyCode::Char('.').into());
+        assert_eq!(app.todo_list.items[3].priority, Priority::High);
+        assert_eq!(app.visible(), vec![2, 3, 0, 1]);
+
+        // Wraps around to low
+        app.handle_list_key_event(KeyCode::Char('!').into());
+        assert_eq!(app.todo_list.items[3].priority, Priority::Low);
+        assert_eq!(app.visible(), vec![2, 0, 3, 1]);
+        assert_eq!(app.selected_index(), Some(3));
+        app.handle_list_key_event(KeyCode::Char('S').into());
+        assert!(app.sort == Sort::Manual);
+        assert_eq!(app.selected_index(), Some(3));
+    }
+
+    #[test]
+    fn info_preview_in_list() {
+        let mut item = TodoItem::new(Status::Todo, "a", "första raden är lång\nsecond");
+        let mut config = Config::default();
+        assert_eq!(list_line(&item, &config).to_string(), "· ☐ a");
+
+        config.preview_width = Some(8);
+        assert_eq!(list_line(&item, &config).to_string(), "· ☐ a  första …");
+        item.info = String::from("\nsecond");
+        assert_eq!(list_line(&item, &config).to_string(), "· ☐ a");
+    }
+
+    #[test]
+    fn cycle_tag_filters() {
+        let mut app = App::default();
+        app.todo_list.items = vec![
+            TodoItem::builder().todo("a").tags(["work", "home"]).build(),
+            TodoItem::builder().todo("b").build(),
+            TodoItem::builder()
+                .todo("c")
+                .tags(["errands", "work"])
+                .build(),
+        ];
+        assert_eq!(
+            distinct_tags(&app.todo_list.items),
+            ["errands", "home", "work"]
+        );
+
+        let mut cycle = || {
+            app.handle_list_key_event(KeyCode::Char('t').into());
+            (app.list_title(), app.visible())
+        };
+        assert_eq!(cycle(), (String::from("TODO #errands"), vec![2]));
+        assert_eq!(cycle(), (String::from("TODO #home"), vec![0]));
+        assert_eq!(cycle(), (String::from("TODO #work"), vec![0, 2]));
+        assert_eq!(cycle(), (String::from("TODO"), vec![0, 1, 2]));
+        assert_eq!(cycle(), (String::from("TODO #errands"), vec![2]));
+    }
+
+    #[test]
+    fn surface_failed_saves() {
+        // A file where the data directory should be can't be written to
+        let dir = std::env::temp_dir().join("todo_unwritable");
+        _ = std::fs::create_dir_all(&dir);
+        let blocker = dir.join("blocker");
+        std::fs::write(&blocker, "").unwrap();
+        let mut app = App {
+            data_path: blocker.join("todos.json"),
+            ..app_with(&["a"])
+        };
+
+        app.check_writable();
+        let fallback = data::fallback_path(&app.data_path);
+        assert_eq!(app.fallback_path.as_ref(), Some(&fallback));
+        assert!(
+            app.message
+                .as_ref()
+                .unwrap()
+                .starts_with("Warning: can't save here")
+        );
+
+        assert!(!app.save());
+        assert!(app.message.as_ref().unwrap().starts_with("Failed to save"));
+        // The error stays after the message is gone
+        app.handle_key_event(KeyCode::Char('j').into());
+        assert!(app.message.is_none());
+        assert!(
+            app.error
+                .as_ref()
+                .unwrap()
+                .starts_with("Failed to save: File error")
+        );
+
+        // The first quit warns, the second one quits anyway
+        app.handle_list_key_event(KeyCode::Char('q').into());
+        assert!(!app.exit);
+        assert!(
+            app.message
+                .as_ref()
+                .unwrap()
+                .ends_with("[q] again quits without saving")
+        );
+        app.handle_list_key_event(KeyCode::Char('q').into());
+        assert!(app.exit);
+
+        // Unless saving elsewhere works out
+        app.exit = false;
+        _ = std::fs::remove_file(&fallback);
+        app.handle_list_key_event(KeyCode::Char('W').into());
+        assert_eq!(app.data_path, fallback);
+        assert!(app.error.is_none());
+        assert_eq!(data::read_todos(&fallback).unwrap().items().len(), 1);
+        app.handle_list_key_event(KeyCode::Char('q').into());
+        assert!(app.exit);
+    }
+
+    #[test]
+    fn guard_empty_title() {
+        let mut app = app_with(&["milk"]);
+        app.todo_list.state.select(Some(0));
+        app.config.empty_title = EmptyTitle::Keep;
+        app.edit_entry();
+        app.input = Input::default();
+        app.handle_edit_key_event(KeyCode::Char('j').into());
+        assert_eq!(app.focus, Some(Focus::Todo));
+        assert_eq!(app.todo_list.items[0].todo, "milk");
+        assert_eq!(app.message.as_deref(), Some("The title can't be empty"));
+
+        app.config.empty_title = EmptyTitle::Restore;
+        app.handle_edit_key_event(KeyCode::Tab.into());
+        assert_eq!(app.focus, Some(Focus::Tags));
+        assert_eq!(app.todo_list.items[0].todo, "milk");
+
+        // Nothing to restore on a new item
+        app.switch_view(View::List);
+        app.add_entry();
+        let index = app.editing_index.unwrap();
+        app.handle_edit_key_event(KeyCode::Esc.into());
+        app.handle_edit_key_event(KeyCode::Char('j').into());
+        assert_eq!(app.focus, Some(Focus::Todo));
+
+        app.config.empty_title = EmptyTitle::Allow;
+        app.handle_edit_key_event(KeyCode::Char('j').into());
+        assert_eq!(app.focus, Some(Focus::Tags));
+        assert_eq!(app.todo_list.items[index].todo, "");
+
+        // Clearing the title with dd is guarded too
+        app.todo_list.items[0].id = 1;
+        app.todo_list.state.select(Some(0));
+        app.switch_view(View::List);
+        app.edit_entry();
+        app.config.empty_title = EmptyTitle::Keep;
+        app.handle_edit_key_event(KeyCode::Char('d').into());
+        app.handle_edit_key_event(KeyCode::Char('d').into());
+        assert_eq!(app.todo_list.items[0].todo, "milk");
+        assert_eq!(app.input.value(), "milk");
+        assert_eq!(app.message.as_deref(), Some("The title can't be empty"));
+
+        app.config.empty_title = EmptyTitle::Restore;
+        app.handle_edit_key_event(KeyCode::Char('d').into());
+        app.handle_edit_key_event(KeyCode::Char('d').into());
+        assert_eq!(app.todo_list.items[0].todo, "milk");
+        assert_eq!(app.message.as_deref(), Some("Restored the empty title"));
+    }
+
+    #[test]
+    fn editor_footer_hints() {
+        let normal = editor_footer(&EditMode::Normal, &View::Edit, 80);
+        assert_eq!(normal, " NORMAL Mode  i:insert q:back Tab:field");
+        let insert = editor_footer(&EditMode::Insert, &View::Edit, 80);
+        assert_eq!(insert, " INSERT Mode  Esc:normal");
+        let scratch = editor_footer(&EditMode::Normal, &View::Scratch, 80);
+        assert_eq!(scratch, " NORMAL Mode  i:insert q:back");
+        // Narrow modals cut the hints short
+        assert_eq!(
+            editor_footer(&EditMode::Normal, &View::Edit, 20),
+            " NORMAL Mode  i:in…"
+        );
+    }
+
+    #[test]
+    fn log_time_on_completion() {
+        let mut app = app_with(&["a", "b"]);
+        app.todo_list.items[0].id = 1;
+        app.todo_list.items[1].id = 2;
+        app.config.log_time_on_complete = true;
+        app.todo_list.state.select(Some(0));
+        app.handle_list_key_event(KeyCode::Char('c').into());
+        assert!(app.time_prompt.is_some());
+        for c in ['4', 'x', '5'] {
+            app.handle_list_key_event(KeyCode::Char(c).into());
         }
+        // The time goes to the completed item, even if it moved in the meantime
+        app.todo_list.items.swap(0, 1);
+        app.handle_list_key_event(KeyCode::Enter.into());
+        assert!(app.time_prompt.is_none());
+        assert_eq!(app.todo_list.items[1].actual_minutes, Some(45));
+        assert_eq!(app.todo_list.items[0].actual_minutes, None);
+        app.todo_list.items.swap(0, 1);
+
+        // Skipping leaves it unset
+        app.todo_list.state.select(Some(1));
+        app.handle_list_key_event(KeyCode::Char('c').into());
+        app.handle_list_key_event(KeyCode::Char('9').into());
+        app.handle_list_key_event(KeyCode::Esc.into());
+        assert!(matches!(app.todo_list.items[1].status, Status::Completed));
+        assert_eq!(app.todo_list.items[1].actual_minutes, None);
+
+        // Reopening doesn't ask
+        app.handle_list_key_event(KeyCode::Char('c').into());
+        assert!(app.time_prompt.is_none());
     }
-}
 
-impl FromIterator<(Status, &'static str, &'static str)> for TodoList {
-    fn from_iter<I: IntoIterator<Item = (Status, &'static str, &'static str)>>(iter: I) -> Self {
-        let items = iter
-            .into_iter()
-            .map(|(status, todo, info)| TodoItem::new(status, todo, info))
+    #[test]
+    fn command_palette() {
+        let names: HashSet<&str> = PALETTE_COMMANDS.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names.len(), PALETTE_COMMANDS.len());
+
+        assert_eq!(fuzzy_score("Toggle status", "tgst"), Some(5));
+        assert_eq!(fuzzy_score("Toggle status", "toggle"), Some(0));
+        assert_eq!(fuzzy_score("Toggle status", "xyz"), None);
+        let name = |index: usize| PALETTE_COMMANDS[index].0;
+        let matches = palette_matches("sort");
+        assert_eq!(name(matches[0]), "Cycle sort");
+        let matches = palette_matches("DEL");
+        assert_eq!(name(matches[0]), "Delete item");
+        assert!(
+            matches
+                .iter()
+                .all(|&i| fuzzy_score(name(i), "del").is_some())
+        );
+        assert_eq!(palette_matches("").len(), PALETTE_COMMANDS.len());
+
+        // Running a command by name
+        let mut app = app_with(&["a", "b"]);
+        app.todo_list.state.select(Some(1));
+        app.handle_list_key_event(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL));
+        for c in "toggle st".chars() {
+            app.handle_list_key_event(KeyCode::Char(c).into());
+        }
+        app.handle_list_key_event(KeyCode::Enter.into());
+        assert!(app.palette.is_none());
+        assert!(matches!(app.todo_list.items[1].status, Status::Completed));
+
+        // Nothing matches, nothing runs
+        app.handle_list_key_event(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL));
+        app.handle_list_key_event(KeyCode::Char('#').into());
+        app.handle_list_key_event(KeyCode::Enter.into());
+        assert_eq!(app.todo_list.items.len(), 2);
+    }
+
+    #[test]
+    fn jump_by_status() {
+        let mut app = app_with(&["a", "b", "c", "d", "e"]);
+        app.todo_list.items[1].set_status(Status::Completed, Local::now());
+        app.todo_list.items[3].set_status(Status::Completed, Local::now());
+        let items = &app.todo_list.items;
+        let visible = app.visible();
+        assert_eq!(
+            jump_position(items, &visible, &Status::Todo, false),
+            Some(0)
+        );
+        assert_eq!(jump_position(items, &visible, &Status::Todo, true), Some(4));
+        assert_eq!(
+            jump_position(items, &visible, &Status::Completed, false),
+            Some(1)
+        );
+        assert_eq!(
+            jump_position(items, &visible, &Status::Completed, true),
+            Some(3)
+        );
+
+        app.handle_list_key_event(KeyCode::Char('}').into());
+        assert_eq!(app.todo_list.state.selected(), Some(3));
+        app.handle_list_key_event(KeyCode::Char('[').into());
+        assert_eq!(app.todo_list.state.selected(), Some(0));
+
+        // Nothing to jump to
+        app.todo_list.items[1].set_status(Status::Todo, Local::now());
+        app.todo_list.items[3].set_status(Status::Todo, Local::now());
+        app.handle_list_key_event(KeyCode::Char('{').into());
+        assert_eq!(app.todo_list.state.selected(), Some(0));
+    }
+
+    #[test]
+    fn sort_by_key_chain() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 1, d).unwrap();
+        let mut app = App::default();
+        app.todo_list.items = vec![
+            TodoItem::builder().todo("delta").due(day(2)).build(),
+            TodoItem::builder().todo("Charlie").build(),
+            TodoItem::builder().todo("bravo").due(day(2)).build(),
+            TodoItem::builder()
+                .todo("alpha")
+                .status(Status::Completed)
+                .build(),
+            TodoItem::builder().todo("echo").due(day(1)).build(),
+        ];
+        app.config.sort_keys = vec![SortKey::Status, SortKey::Due, SortKey::Title];
+        app.cycle_sort();
+        app.cycle_sort();
+        assert!(app.sort == Sort::Keys);
+        assert_eq!(app.visible(), vec![4, 2, 0, 1, 3]);
+
+        // Ties on every key keep the manual order
+        app.config.sort_keys = vec![SortKey::Status];
+        assert_eq!(app.visible(), vec![0, 1, 2, 4, 3]);
+
+        // Nothing to cycle to without keys
+        app.config.sort_keys.clear();
+        app.cycle_sort();
+        app.cycle_sort();
+        assert!(app.sort == Sort::Manual);
+    }
+
+    #[test]
+    fn sort_by_modified() {
+        let mut app = app_with(&["a", "b", "c"]);
+        app.todo_list.state.select(Some(2));
+        app.edit_entry();
+        app.input = Input::new(String::from("c edited"));
+        app.save_input();
+        assert!(app.todo_list.items[2].modified_at.is_some());
+        assert!(app.todo_list.items[0].modified_at.is_none());
+
+        // Saving an unchanged field is not a modification
+        let modified_at = app.todo_list.items[2].modified_at;
+        app.save_input();
+        assert_eq!(app.todo_list.items[2].modified_at, modified_at);
+
+        app.switch_view(View::List);
+        app.todo_list.state.select(Some(0));
+        app.toggle_status();
+        app.cycle_sort();
+        assert_eq!(app.visible(), vec![1, 2, 0]);
+        assert_eq!(app.selected_index(), Some(0));
+    }
+
+    #[test]
+    fn status_filter_keys() {
+        let mut app = app_with(&["a", "b", "ab", "c"]);
+        app.todo_list.items[1].set_status(Status::Completed, Local::now());
+        app.todo_list.items[2].set_status(Status::Completed, Local::now());
+
+        app.handle_list_key_event(KeyCode::Char('1').into());
+        assert_eq!(app.visible(), vec![0, 3]);
+        assert_eq!(app.list_title(), "TODO [todo]");
+        app.handle_list_key_event(KeyCode::Char('2').into());
+        assert_eq!(app.visible(), vec![1, 2]);
+
+        // The status filter intersects with the search
+        app.search = Input::new(String::from("a"));
+        assert_eq!(app.visible(), vec![2]);
+        app.handle_list_key_event(KeyCode::Char('0').into());
+        assert_eq!(app.visible(), vec![0, 2]);
+        assert_eq!(app.list_title(), "TODO");
+    }
+
+    #[test]
+    fn separator_theme() {
+        let area = Rect::new(0, 0, 12, 1);
+        let render = |theme: &Theme| {
+            let mut buf = ratatui::buffer::Buffer::empty(area);
+            separator_block(theme).render(area, &mut buf);
+            buf
+        };
+        let buf = render(&Theme::default());
+        assert_eq!(buf[(0, 0)].symbol(), "─");
+        assert_eq!(buf[(0, 0)].fg, Color::White);
+
+        let theme = Theme {
+            separator: Separator::Dotted,
+            separator_color: Some(Color::Magenta),
+            separator_label: Some(String::from("notes")),
+            ..Default::default()
+        };
+        let buf = render(&theme);
+        assert_eq!(buf[(0, 0)].symbol(), "┄");
+        assert_eq!(buf[(0, 0)].fg, Color::Magenta);
+        let line: String = (0..12).map(|x| buf[(x, 0)].symbol()).collect();
+        assert_eq!(line, "┄┄ notes ┄┄┄");
+    }
+
+    #[test]
+    fn ascii_mode() {
+        let theme = Theme {
+            ascii: Some(true),
+            ..Default::default()
+        };
+        let area = Rect::new(0, 0, 10, 3);
+        let mut buf = ratatui::buffer::Buffer::empty(area);
+        themed_block(&theme, "").render(area, &mut buf);
+        assert_eq!(buf[(0, 0)].symbol(), "+");
+        assert_eq!(buf[(1, 0)].symbol(), "-");
+        assert_eq!(buf[(0, 1)].symbol(), "|");
+
+        assert_eq!(checkbox(&Status::Todo, &theme), "[ ]");
+        assert_eq!(checkbox(&Status::Completed, &theme), "[x]");
+        assert_eq!(checkbox(&Status::InProgress, &theme), "[~]");
+        assert_eq!(checkbox(&Status::Todo, &Theme::default()), "☐");
+
+        assert!(config::is_ascii_terminal(Some("xterm"), Some("C")));
+        assert!(config::is_ascii_terminal(Some("dumb"), Some("en_US.UTF-8")));
+        assert!(!config::is_ascii_terminal(
+            Some("xterm"),
+            Some("en_US.utf8")
+        ));
+        assert!(!config::is_ascii_terminal(Some("xterm"), None));
+    }
+
+    #[test]
+    fn insert_below_and_above() {
+        let mut app = app_with(&["a", "b", "c"]);
+        app.todo_list.state.select(Some(1));
+        app.handle_list_key_event(KeyCode::Char('o').into());
+        assert_eq!(app.editing_index, Some(2));
+        assert_eq!(app.todo_list.items[2].todo, "");
+        assert_eq!(app.todo_list.items[3].todo, "c");
+        assert_eq!(app.todo_list.state.selected(), Some(2));
+
+        app.switch_view(View::List);
+        app.todo_list.state.select(Some(0));
+        app.handle_list_key_event(KeyCode::Char('O').into());
+        assert_eq!(app.editing_index, Some(0));
+        assert_eq!(app.todo_list.items[1].todo, "a");
+        assert_eq!(app.todo_list.items.len(), 5);
+    }
+
+    #[test]
+    fn debounce_flush() {
+        let start = Instant::now();
+        let debounce = Duration::from_millis(500);
+        let at = |millis| start + Duration::from_millis(millis);
+        // A burst of changes keeps pushing the flush back
+        let mut last_change = None;
+        for change in [0, 100, 200] {
+            assert!(!should_flush(last_change, at(change), debounce));
+            last_change = Some(at(change));
+        }
+        assert!(!should_flush(last_change, at(600), debounce));
+        assert!(should_flush(last_change, at(700), debounce));
+        assert!(!should_flush(None, at(700), debounce));
+    }
+
+    #[test]
+    fn command_line_toggle_range() {
+        let mut app = app_with(&["a", "b", "c", "d", "e", "f"]);
+        app.run_command("2,4 toggle");
+        let completed: Vec<bool> = app
+            .todo_list
+            .items
+            .iter()
+            .map(|item| matches!(item.status, Status::Completed))
             .collect();
-        let state = ListState::default();
-        Self { items, state }
+        assert_eq!(completed, vec![false, true, true, true, false, false]);
+        assert_eq!(app.message, None);
+
+        app.run_command("5,9x");
+        assert!(matches!(app.todo_list.items[5].status, Status::Completed));
+        assert_eq!(
+            app.message.as_deref(),
+            Some("Warning: range clamped to 5,6")
+        );
     }
-}
 
-impl From<&TodoItem> for ListItem<'_> {
-    fn from(value: &TodoItem) -> Self {
-        let text = match value.status {
-            Status::Todo => Span::raw(format!("☐ {}", value.todo)),
-            Status::Completed => Span::styled(format!("✓ {}", value.todo), COMPLETED_STYLE),
+    #[test]
+    fn remember_last_focus() {
+        let mut app = app_with(&["a", "b"]);
+        app.todo_list.items[0].id = 1;
+        app.todo_list.items[0].info = String::from("details");
+        app.todo_list.items[1].id = 2;
+        app.todo_list.state.select(Some(0));
+        app.edit_entry();
+        app.switch_focus(Focus::Info);
+        app.switch_view(View::List);
+
+        // Off by default, so editing starts on the title
+        app.edit_entry();
+        assert!(app.focus == Some(Focus::Todo));
+        app.switch_focus(Focus::Info);
+        app.switch_view(View::List);
+
+        app.config.remember_focus = true;
+        app.edit_entry();
+        assert!(app.focus == Some(Focus::Info));
+        assert_eq!(app.input.value(), "details");
+        app.switch_view(View::List);
+
+        // Other items still start on the title
+        app.todo_list.state.select(Some(1));
+        app.edit_entry();
+        assert!(app.focus == Some(Focus::Todo));
+    }
+
+    #[test]
+    fn demote_and_promote() {
+        let mut items: Vec<TodoItem> = ["a", "b", "c"]
+            .iter()
+            .map(|&t| TodoItem::new(Status::Todo, t, ""))
+            .collect();
+        assert_eq!(demote(&mut items, 0, 2), 1);
+        let todos: Vec<&str> = items.iter().map(|item| item.todo.as_str()).collect();
+        assert_eq!(todos, vec!["b", "c"]);
+        assert_eq!(items[1].subtasks[0].todo, "a");
+
+        assert_eq!(promote(&mut items, 1), Some(2));
+        let todos: Vec<&str> = items.iter().map(|item| item.todo.as_str()).collect();
+        assert_eq!(todos, vec!["b", "c", "a"]);
+        assert_eq!(promote(&mut items, 1), None);
+
+        let mut app = app_with(&["a", "b", "c"]);
+        app.todo_list.state.select(Some(2));
+        app.handle_list_key_event(KeyCode::Char('>').into());
+        app.handle_list_key_event(KeyCode::Char('k').into());
+        app.handle_list_key_event(KeyCode::Enter.into());
+        assert_eq!(app.todo_list.items.len(), 2);
+        assert_eq!(app.todo_list.items[1].subtasks[0].todo, "c");
+        assert_eq!(app.selected_index(), Some(1));
+    }
+
+    #[test]
+    fn unique_ids_after_promote() {
+        let mut app = app_with(&["a", "b"]);
+        app.todo_list.items[0].id = 1;
+        app.todo_list.items[1].id = 2;
+        demote(&mut app.todo_list.items, 1, 0);
+
+        // The id of the subtask is taken too
+        app.insert_entry(1);
+        assert_eq!(app.todo_list.items[1].id, 3);
+        promote(&mut app.todo_list.items, 0);
+        let ids: Vec<u64> = app.todo_list.items.iter().map(|item| item.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn unique_ids_across_lists() {
+        let mut app = app_with(&["a"]);
+        app.todo_list.items[0].id = 1;
+        let mut other = TodoItem::new(Status::Todo, "b", "");
+        other.id = 5;
+        app.lists.insert(String::from("work"), vec![other]);
+
+        app.insert_entry(1);
+        assert_eq!(app.todo_list.items[1].id, 6);
+    }
+
+    #[test]
+    fn add_while_filtered() {
+        let mut app = app_with(&["apple", "banana", "cherry"]);
+        app.todo_list.items[1].set_status(Status::Completed, Local::now());
+        app.search = Input::new(String::from("an"));
+        app.status_filter = StatusFilter::Completed;
+        app.todo_list.state.select(Some(0));
+
+        app.add_entry();
+        assert_eq!(app.search.value(), "");
+        assert!(app.visible().contains(&3));
+        assert_eq!(app.selected_index(), Some(3));
+        assert_eq!(app.editing_index, Some(3));
+
+        // Without clearing, the filter stays and a warning is shown
+        app.switch_view(View::List);
+        app.config.add_clears_filter = false;
+        app.status_filter = StatusFilter::Completed;
+        app.add_entry();
+        assert!(!app.visible().contains(&4));
+        assert_eq!(
+            app.message.as_deref(),
+            Some("Warning: the new item is hidden by the filter")
+        );
+    }
+
+    #[test]
+    fn numeric_chords() {
+        let mut app = app_with(&["a", "b", "c", "d"]);
+        app.config.numeric_chords = true;
+        // x cycles the status, like it does without a number
+        app.handle_list_key_event(KeyCode::Char('3').into());
+        app.handle_list_key_event(KeyCode::Char('x').into());
+        assert_eq!(app.todo_list.items[2].status, Status::InProgress);
+        app.handle_list_key_event(KeyCode::Char('3').into());
+        app.handle_list_key_event(KeyCode::Char('x').into());
+        let completed: Vec<bool> = app
+            .todo_list
+            .items
+            .iter()
+            .map(|item| matches!(item.status, Status::Completed))
+            .collect();
+        assert_eq!(completed, vec![false, false, true, false]);
+
+        app.handle_list_key_event(KeyCode::Char('1').into());
+        app.handle_list_key_event(KeyCode::Char('d').into());
+        app.handle_list_key_event(KeyCode::Char('y').into());
+        assert_eq!(app.todo_list.items.len(), 3);
+        assert_eq!(app.todo_list.items[0].todo, "b");
+
+        // A cancelled chord does nothing
+        app.handle_list_key_event(KeyCode::Char('2').into());
+        app.handle_list_key_event(KeyCode::Esc.into());
+        app.handle_list_key_event(KeyCode::Char('x').into());
+        assert!(matches!(app.todo_list.items[1].status, Status::Completed));
+
+        // A lone digit still filters once the chord times out
+        app.handle_list_key_event(KeyCode::Char('2').into());
+        assert!(app.status_filter == StatusFilter::All);
+        app.on_tick(Instant::now() + CHORD_TIMEOUT);
+        assert!(app.status_filter == StatusFilter::Completed);
+        app.handle_list_key_event(KeyCode::Char('1').into());
+        app.handle_list_key_event(KeyCode::Char('2').into());
+        app.on_tick(Instant::now() + CHORD_TIMEOUT);
+        assert!(app.status_filter == StatusFilter::Completed);
+        app.handle_list_key_event(KeyCode::Char('0').into());
+        app.on_tick(Instant::now() + CHORD_TIMEOUT);
+        assert!(app.status_filter == StatusFilter::All);
+    }
+
+    #[test]
+    fn reset_view_shows_everything() {
+        let mut app = app_with(&["a", "b", "ab", "c"]);
+        app.todo_list.items[2].set_status(Status::Completed, Local::now());
+        app.search = Input::new(String::from("a"));
+        app.status_filter = StatusFilter::Completed;
+        app.sort = Sort::Modified;
+        app.todo_list.state.select(Some(0));
+        assert_eq!(app.visible(), vec![2]);
+
+        app.handle_list_key_event(KeyCode::Char('\\').into());
+        assert_eq!(app.visible(), vec![0, 1, 2, 3]);
+        assert_eq!(app.selected_index(), Some(2));
+        assert_eq!(app.list_title(), "TODO");
+        assert_eq!(app.todo_list.items.len(), 4);
+    }
+
+    #[test]
+    fn completion_gauge_ratio() {
+        assert_eq!(completion_ratio(&[]), 0.0);
+        let mut app = app_with(&["a", "b", "c", "d"]);
+        assert_eq!(completion_ratio(&app.todo_list.items), 0.0);
+        app.todo_list.items[0].set_status(Status::Completed, Local::now());
+        assert_eq!(completion_ratio(&app.todo_list.items), 0.25);
+        for item in &mut app.todo_list.items {
+            item.set_status(Status::Completed, Local::now());
+        }
+        assert_eq!(completion_ratio(&app.todo_list.items), 1.0);
+    }
+
+    #[test]
+    fn due_soon_notifications() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+        assert!(should_notify(day(10), today, 1, false));
+        assert!(should_notify(day(11), today, 1, false));
+        assert!(!should_notify(day(12), today, 1, false));
+        assert!(should_notify(day(1), today, 1, false));
+        assert!(!should_notify(day(10), today, 1, true));
+
+        let mut app = app_with(&["pay rent", "later"]);
+        app.config.due_soon_days = Some(1);
+        app.todo_list.items[0].id = 1;
+        app.todo_list.items[0].due = Some(day(11));
+        app.todo_list.items[1].id = 2;
+        app.todo_list.items[1].due = Some(day(20));
+        app.notify_due_soon(today);
+        assert_eq!(app.message.as_deref(), Some("Due soon: pay rent"));
+
+        // Only once per session
+        app.message = None;
+        app.notify_due_soon(today);
+        assert_eq!(app.message, None);
+    }
+
+    #[test]
+    fn due_soon_on_tick() {
+        let morning = Local.with_ymd_and_hms(2024, 3, 10, 9, 0, 0).unwrap();
+        let clock = Rc::new(FixedClock::new(morning));
+        let mut app = app_with(&["pay rent"]);
+        app.clock = Box::new(Rc::clone(&clock));
+        app.config.due_soon_days = Some(1);
+        app.todo_list.items[0].id = 1;
+        app.todo_list.items[0].due = NaiveDate::from_ymd_opt(2024, 3, 12);
+
+        app.on_tick(Instant::now());
+        assert_eq!(app.message, None);
+
+        // A day later it is due tomorrow
+        clock.advance(TimeDelta::days(1));
+        app.on_tick(Instant::now());
+        assert_eq!(app.message.as_deref(), Some("Due soon: pay rent"));
+    }
+
+    #[test]
+    fn completed_at_from_clock() {
+        let morning = Local.with_ymd_and_hms(2024, 3, 10, 9, 0, 0).unwrap();
+        let clock = Rc::new(FixedClock::new(morning));
+        let mut app = app_with(&["pay rent"]);
+        app.clock = Box::new(Rc::clone(&clock));
+        app.todo_list.state.select(Some(0));
+
+        app.toggle_status();
+        let item = &app.todo_list.items[0];
+        assert_eq!(item.completed_at, Some(morning));
+        assert_eq!(item.modified_at, Some(morning));
+
+        // Reopening and completing again records the new time
+        clock.advance(TimeDelta::hours(2));
+        app.toggle_status();
+        app.toggle_status();
+        let item = &app.todo_list.items[0];
+        assert_eq!(item.completed_at, Some(morning + TimeDelta::hours(2)));
+    }
+
+    #[test]
+    fn surface_failed_loads() {
+        let dir = std::env::temp_dir().join("todo_failed_load");
+        _ = std::fs::create_dir_all(&dir);
+        let data_path = dir.join("todos.json");
+        _ = std::fs::remove_file(&data_path);
+        let mut app = App {
+            data_path: data_path.clone(),
+            ..Default::default()
         };
-        ListItem::new(text)
+        app.load();
+        assert!(app.error.is_none());
+
+        std::fs::write(&data_path, "[{\"todo\": ").unwrap();
+        app.load();
+        assert!(
+            app.error
+                .as_ref()
+                .unwrap()
+                .starts_with("Failed to load: Invalid JSON")
+        );
+
+        // A directory can't be read as a file
+        app.data_path = dir;
+        app.load();
+        assert!(
+            app.error
+                .as_ref()
+                .unwrap()
+                .starts_with("Failed to load: File error")
+        );
     }
-}
 
-/// Renders the cursor as needed
-fn render_cursor(f: &mut Frame, pos: (u16, u16)) {
-    f.set_cursor_position(pos)
-}
+    #[test]
+    fn select_first_item_on_load() {
+        let dir = std::env::temp_dir().join("todo_select_on_load");
+        let data_path = dir.join("todos.json");
+        let todos = vec![
+            TodoItem::new(Status::Todo, "a", ""),
+            TodoItem::new(Status::Todo, "b", ""),
+        ];
+        let file = data::TodoFile::new(todos);
+        data::write_todos(&file, &data_path, JsonFormat::Compact).unwrap();
 
-/// Helper function to make a centered area of any size
-fn centered_area(area: Rect, x: u16, y: u16) -> Rect {
-    let vertical = Layout::vertical([Constraint::Length(y)]).flex(Flex::Center);
-    let horizontal = Layout::horizontal([Constraint::Length(x)]).flex(Flex::Center);
-    let [area] = vertical.areas(area);
-    let [area] = horizontal.areas(area);
-    area
-}
+        let mut app = App {
+            data_path,
+            ..Default::default()
+        };
+        app.load();
+        assert_eq!(app.todo_list.state.selected(), Some(0));
+        app.toggle_status();
+        assert!(matches!(app.todo_list.items[0].status, Status::Completed));
+    }
 
-/// Renders the outermost border with appropriate titles
-fn render_border(f: &mut Frame, area: Rect) {
-    let instructions = Line::from(vec![
-        Span::styled(" [Q]", KEYBIND_STYLE),
-        Span::raw("uit "),
-        Span::styled("[A]", KEYBIND_STYLE),
-        Span::raw("dd "),
-        Span::styled("[D]", KEYBIND_STYLE),
-        Span::raw("elete "),
-        Span::styled("[C]", KEYBIND_STYLE),
-        Span::raw("omplete "),
-    ])
-    .centered();
+    #[test]
+    fn switch_between_lists() {
+        let dir = std::env::temp_dir().join("todo_named_lists");
+        let data_path = dir.join("todos.json");
+        _ = std::fs::remove_file(&data_path);
+        let mut app = App {
+            data_path: data_path.clone(),
+            ..app_with(&["a", "b"])
+        };
+        app.load();
+        assert_eq!(app.list_name, data::DEFAULT_LIST);
+        assert_eq!(app.list_title(), "TODO");
+        app.handle_list_key_event(KeyCode::Tab.into());
+        assert_eq!(app.list_name, data::DEFAULT_LIST);
 
-    f.render_widget(
-        Block::bordered()
-            .title(Line::raw(" TODO ").centered())
-            .title_bottom(instructions)
-            .border_type(BorderType::Rounded)
-            .fg(Color::White),
-        area,
-    );
+        // New lists start empty, and the actions work on the active list
+        app.run_command("list work");
+        assert_eq!(app.list_title(), "TODO: work");
+        assert!(app.todo_list.items.is_empty());
+        app.add_entry();
+        app.input = Input::new(String::from("report"));
+        app.switch_view(View::List);
+        assert_eq!(app.todo_list.items.len(), 1);
+
+        app.handle_list_key_event(KeyCode::Tab.into());
+        assert_eq!(app.list_name, data::DEFAULT_LIST);
+        assert_eq!(app.todo_list.items.len(), 2);
+        assert_eq!(app.todo_list.state.selected(), Some(0));
+        app.handle_list_key_event(KeyCode::BackTab.into());
+        assert_eq!(app.list_name, "work");
+        assert_eq!(app.todo_list.items[0].todo, "report");
+
+        // All lists are saved together, along with the active one
+        assert!(app.overwrite());
+        let file = data::read_todos(&data_path).unwrap();
+        assert_eq!(file.list, "work");
+        assert_eq!(file.lists[data::DEFAULT_LIST].len(), 2);
+        assert_eq!(file.items()[0].todo, "report");
+        _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn restore_selection_on_load() {
+        let dir = std::env::temp_dir().join("todo_restore_selection");
+        let data_path = dir.join("todos.json");
+        let mut todos = vec![
+            TodoItem::new(Status::Todo, "a", ""),
+            TodoItem::new(Status::Todo, "b", ""),
+            TodoItem::new(Status::Todo, "c", ""),
+        ];
+        for (id, item) in todos.iter_mut().enumerate() {
+            item.id = id as u64 + 1;
+        }
+        let file = data::TodoFile::new(todos.clone());
+        data::write_todos(&file, &data_path, JsonFormat::Compact).unwrap();
+
+        let mut app = App {
+            data_path: data_path.clone(),
+            ..Default::default()
+        };
+        app.load();
+        app.select_item(2);
+        app.overwrite();
+
+        let mut app = App {
+            data_path: data_path.clone(),
+            ..Default::default()
+        };
+        app.load();
+        assert_eq!(app.selected_index(), Some(2));
+
+        // A selection that no longer exists falls back to the first item
+        let file = data::TodoFile {
+            selected: Some(42),
+            ..data::TodoFile::new(todos)
+        };
+        data::write_todos(&file, &data_path, JsonFormat::Compact).unwrap();
+        let mut app = App {
+            data_path,
+            ..Default::default()
+        };
+        app.load();
+        assert_eq!(app.selected_index(), Some(0));
+        _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn preview_wrap_independent_of_edit() {
+        let mut app = app_with(&["a"]);
+        app.config.preview_wrap = WrapMode::None;
+        app.config.edit_wrap = WrapMode::Word;
+        let info = "A wrap occurs";
+        let area = Rect::new(0, 0, 5, 5);
+
+        let preview = preview_field(info, app.config.preview_wrap);
+        assert_eq!(preview.lines(area), vec!["A wrap occurs"]);
+        let edit = app.input_field(info, &Focus::Info);
+        assert_eq!(edit.lines(area), vec!["A ", "wrap ", "occur", "s"]);
+
+        app.config.preview_wrap = WrapMode::Word;
+        app.config.edit_wrap = WrapMode::None;
+        let preview = preview_field(info, app.config.preview_wrap);
+        assert_eq!(preview.lines(area), vec!["A ", "wrap ", "occur", "s"]);
+        let edit = app.input_field(info, &Focus::Info);
+        assert_eq!(edit.lines(area), vec!["A wrap occurs"]);
+    }
+
+    #[test]
+    fn export_marked_items() {
+        let mut app = app_with(&["a", "b", "c"]);
+        for (id, item) in app.todo_list.items.iter_mut().enumerate() {
+            item.id = id as u64 + 1;
+        }
+        app.todo_list.state.select(Some(0));
+        app.handle_list_key_event(KeyCode::Char(' ').into());
+        app.todo_list.state.select(Some(2));
+        app.handle_list_key_event(KeyCode::Char(' ').into());
+
+        let path = std::env::temp_dir().join("todo_export_marked.md");
+        app.run_command(&format!("export {}", path.display()));
+        let output = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(output, "- [ ] a\n- [ ] c\n");
+    }
+
+    #[test]
+    fn mark_and_unmark_all() {
+        let mut app = app_with(&["a", "b", "c"]);
+        for (id, item) in app.todo_list.items.iter_mut().enumerate() {
+            item.id = id as u64 + 1;
+        }
+        app.todo_list.items[1].set_status(Status::Completed, Local::now());
+        app.todo_list.state.select(Some(1));
+        app.toggle_mark();
+
+        app.filter_status(StatusFilter::Todo);
+        app.handle_list_key_event(KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL));
+        assert_eq!(app.marked, HashSet::from([1, 3]));
+
+        app.handle_list_key_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL));
+        assert!(app.marked.is_empty());
+    }
 }
@@ -0,0 +1,311 @@
+use crate::app::{Status, TodoItem};
+use crate::config;
+use crate::data;
+use crate::logging;
+use chrono::Local;
+use color_eyre::eyre::{Result, bail};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+/// Represents what the program was asked to do on the command line
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Tui {
+        examples: bool,
+    },
+    Export {
+        format: ExportFormat,
+        path: PathBuf,
+    },
+    Complete {
+        text: String,
+        exact: bool,
+        first: bool,
+    },
+    Note {
+        title: String,
+        text: String,
+        exact: bool,
+        first: bool,
+        replace: bool,
+    },
+}
+
+/// Represents the file formats todos can be exported to
+#[derive(Debug, PartialEq)]
+pub enum ExportFormat {
+    StatsCsv,
+    Markdown,
+    Json,
+}
+
+/// Removes `--file <path>` from the arguments, wherever it is, returning the path
+pub fn take_file(args: &mut Vec<String>) -> Result<Option<PathBuf>> {
+    let Some(flag) = args.iter().position(|arg| arg == "--file") else {
+        return Ok(None);
+    };
+    if flag + 1 >= args.len() {
+        bail!("Missing path after --file.");
+    }
+    let path = args.remove(flag + 1);
+    args.remove(flag);
+    Ok(Some(PathBuf::from(path)))
+}
+
+/// Parses the command line arguments (excluding the program name)
+pub fn parse(args: &[String]) -> Result<Command> {
+    let mut args = args.iter().map(String::as_str);
+    match args.next() {
+        None => Ok(Command::Tui { examples: false }),
+        Some("--with-examples") => Ok(Command::Tui { examples: true }),
+        Some("export") => {
+            let mut format = None;
+            let mut path = None;
+            while let Some(arg) = args.next() {
+                match arg {
+                    "--format" => format = args.next(),
+                    _ => path = Some(PathBuf::from(arg)),
+                }
+            }
+            let format = match format {
+                Some("stats-csv") => ExportFormat::StatsCsv,
+                Some("markdown") => ExportFormat::Markdown,
+                Some("json") => ExportFormat::Json,
+                Some(other) => bail!("Unknown export format '{other}'."),
+                None => bail!("Missing --format for export."),
+            };
+            let Some(path) = path else {
+                bail!("Missing output file for export.");
+            };
+            Ok(Command::Export { format, path })
+        }
+        Some("complete") => {
+            let mut text = None;
+            let mut exact = false;
+            let mut first = false;
+            for arg in args {
+                match arg {
+                    "--exact" => exact = true,
+                    "--first" => first = true,
+                    _ => text = Some(arg.to_string()),
+                }
+            }
+            let Some(text) = text else {
+                bail!("Missing text to match for complete.");
+            };
+            Ok(Command::Complete { text, exact, first })
+        }
+        Some("note") => {
+            let mut positional = Vec::new();
+            let mut exact = false;
+            let mut first = false;
+            let mut replace = false;
+            for arg in args {
+                match arg {
+                    "--exact" => exact = true,
+                    "--first" => first = true,
+                    "--replace" => replace = true,
+                    _ => positional.push(arg.to_string()),
+                }
+            }
+            let [title, text]: [String; 2] = match positional.try_into() {
+                Ok(val) => val,
+                Err(_) => bail!("Expected a title to match and the text of the note."),
+            };
+            Ok(Command::Note {
+                title,
+                text,
+                exact,
+                first,
+                replace,
+            })
+        }
+        Some(other) => bail!("Unknown command '{other}'."),
+    }
+}
+
+/// Runs a non-interactive command on the given todos file (or the configured one)
+/// Exits with a failure code when complete or note finds no matching todo
+pub fn run(command: Command, file: Option<&Path>) -> Result<ExitCode> {
+    let config = match config::read_config() {
+        Ok(config) => config.unwrap_or_default(),
+        Err(err) => {
+            eprintln!("Failed to read config, using the defaults: {err}");
+            config::Config::default()
+        }
+    };
+    let data_path = data::resolve_path(&config, file)?;
+    _ = logging::init_from_env(&data_path.with_file_name(logging::LOG_FILE));
+    // Commands act on the active list
+    let mut todo_file = data::read_todos(&data_path)?;
+    data::dedup_ids(&mut todo_file);
+    let mut todos = todo_file.take_items();
+
+    match command {
+        Command::Tui { .. } => {}
+        Command::Export { format, path } => {
+            let output = match format {
+                ExportFormat::StatsCsv => data::export_completion_stats_csv(&todos),
+                ExportFormat::Markdown => data::export_markdown(&todos),
+                ExportFormat::Json => data::export_json(&todos)?,
+            };
+            std::fs::write(path, output)?;
+        }
+        Command::Complete { text, exact, first } => {
+            let Some(index) = complete(&mut todos, &text, exact, first)? else {
+                println!("No incomplete todo matches '{text}'.");
+                return Ok(ExitCode::FAILURE);
+            };
+            todo_file.lists.insert(todo_file.list.clone(), todos);
+            data::write_todos(&todo_file, &data_path, config.json_format)?;
+            println!("Completed '{}'.", todo_file.items()[index].todo);
+        }
+        Command::Note {
+            title,
+            text,
+            exact,
+            first,
+            replace,
+        } => {
+            let Some(index) = note(&mut todos, &title, &text, exact, first, replace)? else {
+                println!("No todo matches '{title}'.");
+                return Ok(ExitCode::FAILURE);
+            };
+            todo_file.lists.insert(todo_file.list.clone(), todos);
+            data::write_todos(&todo_file, &data_path, config.json_format)?;
+            println!("Noted on '{}'.", todo_file.items()[index].todo);
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Completes the incomplete todo whose title matches the text
+/// Titles contain the text (ignoring case), or equal it when `exact` is set
+/// Several matches are an error unless `first` is set
+/// Returns the index of the completed todo, or None when nothing matched
+pub fn complete(
+    todos: &mut [TodoItem],
+    text: &str,
+    exact: bool,
+    first: bool,
+) -> Result<Option<usize>> {
+    let incomplete = |item: &TodoItem| !matches!(item.status, Status::Completed);
+    let Some(index) = find_match(todos, text, exact, first, incomplete)? else {
+        return Ok(None);
+    };
+    todos[index].set_status(Status::Completed, Local::now());
+    Ok(Some(index))
+}
+
+/// Adds a note on its own line after the info of the todo whose title matches
+/// The note replaces the info instead when `replace` is set
+/// Titles match like they do for complete, regardless of status
+/// Returns the index of the noted todo, or None when nothing matched
+pub fn note(
+    todos: &mut [TodoItem],
+    title: &str,
+    text: &str,
+    exact: bool,
+    first: bool,
+    replace: bool,
+) -> Result<Option<usize>> {
+    let Some(index) = find_match(todos, title, exact, first, |_| true)? else {
+        return Ok(None);
+    };
+    let info = match (replace, todos[index].info.is_empty()) {
+        (true, _) | (false, true) => text.to_string(),
+        (false, false) => format!("{}\n{text}", todos[index].info),
+    };
+    todos[index].set_info(info, Local::now());
+    Ok(Some(index))
+}
+
+/// Returns the index of the todo (among those accepted by the filter) whose title matches
+/// Titles contain the text (ignoring case), or equal it when `exact` is set
+/// Several matches are an error unless `first` is set
+fn find_match(
+    todos: &[TodoItem],
+    text: &str,
+    exact: bool,
+    first: bool,
+    filter: impl Fn(&TodoItem) -> bool,
+) -> Result<Option<usize>> {
+    let lowercase = text.to_lowercase();
+    let matches: Vec<usize> = (0..todos.len())
+        .filter(|&i| filter(&todos[i]))
+        .filter(|&i| match exact {
+            true => todos[i].todo == text,
+            false => todos[i].todo.to_lowercase().contains(&lowercase),
+        })
+        .collect();
+
+    if matches.len() > 1 && !first {
+        bail!(
+            "{} todos match '{text}', use --first to pick the first.",
+            matches.len()
+        );
+    }
+    Ok(matches.first().copied())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn take_file_flag() {
+        let mut args: Vec<String> = ["complete", "--file", "work.json", "milk"]
+            .map(String::from)
+            .to_vec();
+        let file = take_file(&mut args).unwrap();
+        assert_eq!(file, Some(PathBuf::from("work.json")));
+        assert_eq!(args, ["complete", "milk"]);
+        assert_eq!(take_file(&mut args).unwrap(), None);
+
+        let mut args = vec![String::from("--file")];
+        assert!(take_file(&mut args).is_err());
+    }
+
+    #[test]
+    fn complete_matching_todo() {
+        let mut todos = vec![
+            TodoItem::new(Status::Completed, "buy milk", ""),
+            TodoItem::new(Status::Todo, "Buy milk and eggs", ""),
+            TodoItem::new(Status::Todo, "buy milk", ""),
+        ];
+        assert!(complete(&mut todos, "buy milk", false, false).is_err());
+        assert_eq!(
+            complete(&mut todos, "buy milk", true, false).unwrap(),
+            Some(2)
+        );
+        assert!(matches!(todos[2].status, Status::Completed));
+        assert_eq!(complete(&mut todos, "MILK", false, false).unwrap(), Some(1));
+        assert_eq!(complete(&mut todos, "milk", false, true).unwrap(), None);
+    }
+
+    #[test]
+    fn note_on_matching_todo() {
+        let mut todos = vec![
+            TodoItem::new(Status::Todo, "write report", ""),
+            TodoItem::new(Status::Completed, "deploy", "shipped"),
+        ];
+        assert_eq!(
+            note(&mut todos, "REPORT", "outline", false, false, false).unwrap(),
+            Some(0)
+        );
+        assert_eq!(todos[0].info, "outline");
+        note(&mut todos, "report", "draft", false, false, false).unwrap();
+        assert_eq!(todos[0].info, "outline\ndraft");
+        note(&mut todos, "report", "done", false, false, true).unwrap();
+        assert_eq!(todos[0].info, "done");
+
+        // Completed todos can be noted too
+        note(&mut todos, "deploy", "rolled back", true, false, false).unwrap();
+        assert_eq!(todos[1].info, "shipped\nrolled back");
+        assert_eq!(
+            note(&mut todos, "lunch", "-", false, false, false).unwrap(),
+            None
+        );
+        assert!(note(&mut todos, "e", "-", false, false, false).is_err());
+    }
+}
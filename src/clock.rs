@@ -0,0 +1,73 @@
+use chrono::{DateTime, Local};
+#[cfg(test)]
+use std::cell::Cell;
+use std::rc::Rc;
+
+/// Tells the current time, so time dependent logic can run at any time in tests
+pub trait Clock {
+    fn now(&self) -> DateTime<Local>;
+}
+
+/// The system's clock
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// Shares a clock, so a test can keep moving the clock it handed out
+impl<T: Clock> Clock for Rc<T> {
+    fn now(&self) -> DateTime<Local> {
+        self.as_ref().now()
+    }
+}
+
+impl Default for Box<dyn Clock> {
+    fn default() -> Self {
+        Box::new(SystemClock)
+    }
+}
+
+/// A clock stopped at a time, it only moves when advanced
+#[cfg(test)]
+pub struct FixedClock {
+    time: Cell<DateTime<Local>>,
+}
+
+#[cfg(test)]
+impl FixedClock {
+    pub fn new(time: DateTime<Local>) -> Self {
+        Self {
+            time: Cell::new(time),
+        }
+    }
+
+    pub fn advance(&self, by: chrono::TimeDelta) {
+        self.time.set(self.time.get() + by);
+    }
+}
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now(&self) -> DateTime<Local> {
+        self.time.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeDelta, TimeZone};
+
+    #[test]
+    fn fixed_clock_advances() {
+        let start = Local.with_ymd_and_hms(2024, 3, 10, 9, 0, 0).unwrap();
+        let clock = FixedClock::new(start);
+        assert_eq!(clock.now(), start);
+        assert_eq!(clock.now(), start);
+        clock.advance(TimeDelta::hours(2));
+        assert_eq!(clock.now(), start + TimeDelta::hours(2));
+    }
+}
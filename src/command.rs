@@ -0,0 +1,100 @@
+use std::path::PathBuf;
+
+/// Represents a command typed on the List View command line
+#[derive(Debug, PartialEq)]
+pub enum LineCommand {
+    /// Applies a verb to a range of 1-based positions of the displayed items, `1,5 toggle`
+    Range {
+        start: usize,
+        end: usize,
+        verb: Verb,
+    },
+    /// Writes the marked items to a file, `export notes.md`
+    Export { path: PathBuf },
+    /// Switches to a list, creating it if needed, `list work`
+    List { name: String },
+}
+
+/// Represents what a command does to every item in its range
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verb {
+    Toggle,
+}
+
+/// Parses `export <path>`, `list <name>`, or a range ("3" or "1,5") followed by a verb ("toggle" or "x")
+pub fn parse(input: &str) -> Result<LineCommand, String> {
+    let input = input.trim();
+    if let Some(path) = input.strip_prefix("export") {
+        return match path.trim() {
+            "" => Err(String::from("Missing file to export to")),
+            path => Ok(LineCommand::Export {
+                path: PathBuf::from(path),
+            }),
+        };
+    }
+    if let Some(name) = input.strip_prefix("list") {
+        return match name.trim() {
+            "" => Err(String::from("Missing name of the list")),
+            name => Ok(LineCommand::List {
+                name: String::from(name),
+            }),
+        };
+    }
+    let split = input
+        .find(|c: char| !c.is_ascii_digit() && c != ',')
+        .unwrap_or(input.len());
+    let (range, verb) = input.split_at(split);
+
+    let (start, end) = parse_range(range)?;
+    let verb = match verb.trim() {
+        "toggle" | "x" => Verb::Toggle,
+        "" => return Err(String::from("Missing command after the range")),
+        other => return Err(format!("Unknown command '{other}'")),
+    };
+    Ok(LineCommand::Range { start, end, verb })
+}
+
+/// Parses "N" or "N,M" into an inclusive range of positions
+fn parse_range(range: &str) -> Result<(usize, usize), String> {
+    let number = |text: &str| match text.parse() {
+        Ok(val) => Ok(val),
+        Err(_) => Err(format!("Invalid range '{range}'")),
+    };
+    match range.split_once(',') {
+        Some((start, end)) => Ok((number(start)?, number(end)?)),
+        None => {
+            let line = number(range)?;
+            Ok((line, line))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commands() {
+        let toggle = |start, end| LineCommand::Range {
+            start,
+            end,
+            verb: Verb::Toggle,
+        };
+        assert_eq!(parse("1,5 toggle"), Ok(toggle(1, 5)));
+        assert_eq!(parse("1,5x"), Ok(toggle(1, 5)));
+        assert_eq!(parse(" 3 x "), Ok(toggle(3, 3)));
+        assert!(parse("1,5").is_err());
+        assert!(parse(",5 x").is_err());
+        assert!(parse("1,5 delete").is_err());
+        let export = LineCommand::Export {
+            path: PathBuf::from("out.md"),
+        };
+        assert_eq!(parse("export out.md"), Ok(export));
+        assert!(parse("export").is_err());
+        let list = LineCommand::List {
+            name: String::from("home chores"),
+        };
+        assert_eq!(parse("list home chores "), Ok(list));
+        assert!(parse("list").is_err());
+    }
+}
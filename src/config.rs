@@ -0,0 +1,545 @@
+use crate::data::Error;
+use crate::widget::Wrap;
+use directories::BaseDirs;
+use ratatui::style::Color;
+use ratatui::widgets::BorderType;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::io::prelude::*;
+use std::path::PathBuf;
+
+/// User settings read from the config file, any missing value uses its default
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Config {
+    pub profile: Option<String>,
+    pub profiles: BTreeMap<String, PathBuf>,
+    pub delete_selection: DeleteSelection,
+    pub modal: ModalSize,
+    pub hyperlinks: bool,
+    pub auto_save_secs: Option<u64>,
+    pub auto_complete_parents: bool,
+    pub confirm_incomplete_subtasks: bool,
+    pub confirm_delete: bool,
+    pub complete_subtasks: bool,
+    pub theme: Theme,
+    pub search_commit: SearchCommit,
+    pub feedback: Feedback,
+    pub tag_order: TagOrder,
+    pub priority_tags: Vec<String>,
+    pub saved_queries: Vec<SavedQuery>,
+    pub multiline_paste: MultilinePaste,
+    pub sort_keys: Vec<SortKey>,
+    pub trim_whitespace: bool,
+    pub capitalize_todos: bool,
+    pub preview_width: Option<usize>,
+    pub tag_filter_key: char,
+    pub empty_title: EmptyTitle,
+    pub starter_examples: bool,
+    pub log_time_on_complete: bool,
+    pub count_badge: bool,
+    pub expand_selected: bool,
+    pub max_todos: Option<usize>,
+    pub print_on_exit: bool,
+    pub save_on_change: bool,
+    pub save_debounce_ms: u64,
+    pub remember_focus: bool,
+    pub add_clears_filter: bool,
+    pub numeric_chords: bool,
+    pub progress_gauge: bool,
+    pub due_soon_days: Option<i64>,
+    pub preview_wrap: WrapMode,
+    pub edit_wrap: WrapMode,
+    pub soft_wrap_motions: bool,
+    pub json_format: JsonFormat,
+    pub info_line_numbers: bool,
+}
+
+/// Represents what pressing Enter does to a search
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchCommit {
+    /// Keep the filter and select the top match
+    #[default]
+    Select,
+    /// Also open the top match in Detail View
+    Open,
+}
+
+/// Represents the feedback given when a key does nothing
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Feedback {
+    #[default]
+    Off,
+    Flash,
+    Bell,
+}
+
+/// Represents the order tags are displayed in
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TagOrder {
+    #[default]
+    Insertion,
+    Alphabetical,
+    Priority,
+}
+
+/// Represents how text that doesn't fit on a line is wrapped
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WrapMode {
+    None,
+    Character,
+    #[default]
+    Word,
+}
+
+/// Represents how the todos file is formatted
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonFormat {
+    #[default]
+    Compact,
+    Pretty,
+}
+
+/// Bounds on the size of the Edit View modal
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct ModalSize {
+    pub min_width: u16,
+    pub min_height: u16,
+    pub max_width: u16,
+    pub max_height: u16,
+}
+
+/// Highlights the items matching every field that is set, in List View
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct SavedQuery {
+    /// A tag the item has (without the leading '#')
+    pub tag: Option<String>,
+    /// Text found in the title or info (ignoring case)
+    pub text: Option<String>,
+    pub status: Option<QueryStatus>,
+    pub color: Color,
+}
+
+/// Represents the status a saved query matches
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum QueryStatus {
+    /// Not completed, in progress included
+    Todo,
+    InProgress,
+    Completed,
+}
+
+/// Represents what pasting several lines into the Todo field does
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MultilinePaste {
+    /// The first line is the title, the rest goes into the info
+    #[default]
+    Info,
+    /// Every other line becomes an item of its own
+    Items,
+}
+
+/// Represents a key items are sorted by, later keys break the ties of earlier ones
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortKey {
+    /// Least recently modified first
+    Modified,
+    /// Incomplete items first
+    Status,
+    /// Soonest due first, items without a due date last
+    Due,
+    /// Alphabetical (ignoring case)
+    Title,
+    /// Highest priority first
+    Priority,
+}
+
+/// Represents what happens when focus leaves an empty Todo field
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmptyTitle {
+    /// The empty title is saved
+    #[default]
+    Allow,
+    /// The focus stays on the Todo field
+    Keep,
+    /// The previous title comes back (or else the focus stays)
+    Restore,
+}
+
+/// Represents which item gets selected after a deletion
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeleteSelection {
+    #[default]
+    Next,
+    Previous,
+}
+
+/// Visual settings used while rendering
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Theme {
+    pub border: Border,
+    pub list_title: String,
+    pub edit_title: String,
+    pub prompts: bool,
+    pub prompt_color: Color,
+    pub ascii: Option<bool>,
+    pub preset: Option<Preset>,
+    pub border_color: Option<Color>,
+    pub selected_color: Option<Color>,
+    pub completed_color: Option<Color>,
+    pub in_progress_color: Option<Color>,
+    pub separator: Separator,
+    pub separator_color: Option<Color>,
+    pub separator_label: Option<String>,
+}
+
+/// Represents a curated set of colors, colors set in the config take precedence
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    Dark,
+    Light,
+    Solarized,
+}
+
+/// The colors used while rendering, resolved from the preset and the config
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub border: Color,
+    pub selected: Color,
+    pub completed: Color,
+    pub in_progress: Color,
+}
+
+/// Represents the line style of borders
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Border {
+    Plain,
+    #[default]
+    Rounded,
+    Double,
+    Thick,
+}
+
+/// Represents the line between the Due and Info fields in Edit View
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Separator {
+    #[default]
+    Plain,
+    Dotted,
+    Double,
+    Thick,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            profile: None,
+            profiles: BTreeMap::new(),
+            delete_selection: DeleteSelection::default(),
+            modal: ModalSize::default(),
+            hyperlinks: true,
+            auto_save_secs: None,
+            auto_complete_parents: false,
+            confirm_incomplete_subtasks: false,
+            confirm_delete: true,
+            complete_subtasks: false,
+            theme: Theme::default(),
+            search_commit: SearchCommit::default(),
+            feedback: Feedback::default(),
+            tag_order: TagOrder::default(),
+            priority_tags: Vec::new(),
+            saved_queries: Vec::new(),
+            multiline_paste: MultilinePaste::default(),
+            sort_keys: Vec::new(),
+            trim_whitespace: true,
+            capitalize_todos: false,
+            preview_width: None,
+            tag_filter_key: 't',
+            empty_title: EmptyTitle::default(),
+            starter_examples: false,
+            log_time_on_complete: false,
+            count_badge: false,
+            expand_selected: false,
+            max_todos: None,
+            print_on_exit: false,
+            save_on_change: false,
+            save_debounce_ms: 500,
+            remember_focus: false,
+            add_clears_filter: true,
+            numeric_chords: false,
+            progress_gauge: true,
+            due_soon_days: Some(1),
+            preview_wrap: WrapMode::default(),
+            edit_wrap: WrapMode::default(),
+            soft_wrap_motions: true,
+            json_format: JsonFormat::default(),
+            info_line_numbers: false,
+        }
+    }
+}
+
+impl Default for ModalSize {
+    fn default() -> Self {
+        Self {
+            min_width: 40,
+            min_height: 15,
+            max_width: 80,
+            max_height: 30,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            border: Border::default(),
+            list_title: String::from("TODO"),
+            edit_title: String::new(),
+            prompts: false,
+            prompt_color: Color::DarkGray,
+            ascii: None,
+            preset: None,
+            border_color: None,
+            selected_color: None,
+            completed_color: None,
+            in_progress_color: None,
+            separator: Separator::default(),
+            separator_color: None,
+            separator_label: None,
+        }
+    }
+}
+
+impl Theme {
+    /// Returns the colors of the preset (if any), with the colors set in the config applied
+    pub fn palette(&self) -> Palette {
+        let base = match self.preset {
+            Some(preset) => Palette::from(preset),
+            None => Palette::default(),
+        };
+        Palette {
+            border: self.border_color.unwrap_or(base.border),
+            selected: self.selected_color.unwrap_or(base.selected),
+            completed: self.completed_color.unwrap_or(base.completed),
+            in_progress: self.in_progress_color.unwrap_or(base.in_progress),
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            border: Color::White,
+            selected: Color::DarkGray,
+            completed: Color::Green,
+            in_progress: Color::Yellow,
+        }
+    }
+}
+
+impl From<Preset> for Palette {
+    fn from(value: Preset) -> Self {
+        match value {
+            Preset::Dark => Palette {
+                border: Color::Gray,
+                selected: Color::Rgb(0x44, 0x47, 0x5a),
+                completed: Color::LightGreen,
+                in_progress: Color::LightYellow,
+            },
+            Preset::Light => Palette {
+                border: Color::Black,
+                selected: Color::Rgb(0xd0, 0xd0, 0xd0),
+                completed: Color::Rgb(0x00, 0x80, 0x00),
+                in_progress: Color::Rgb(0xa0, 0x60, 0x00),
+            },
+            Preset::Solarized => Palette {
+                border: Color::Rgb(0x93, 0xa1, 0xa1),
+                selected: Color::Rgb(0x07, 0x36, 0x42),
+                completed: Color::Rgb(0x85, 0x99, 0x00),
+                in_progress: Color::Rgb(0xb5, 0x89, 0x00),
+            },
+        }
+    }
+}
+
+/// Reads the theme preset named by TODUI_THEME
+pub fn preset_from_env() -> Option<Preset> {
+    match std::env::var("TODUI_THEME").ok()?.as_str() {
+        "dark" => Some(Preset::Dark),
+        "light" => Some(Preset::Light),
+        "solarized" => Some(Preset::Solarized),
+        _ => None,
+    }
+}
+
+impl From<WrapMode> for Wrap {
+    fn from(value: WrapMode) -> Self {
+        match value {
+            WrapMode::None => Wrap::None,
+            WrapMode::Character => Wrap::Character,
+            WrapMode::Word => Wrap::Word,
+        }
+    }
+}
+
+impl From<Border> for BorderType {
+    fn from(value: Border) -> Self {
+        match value {
+            Border::Plain => BorderType::Plain,
+            Border::Rounded => BorderType::Rounded,
+            Border::Double => BorderType::Double,
+            Border::Thick => BorderType::Thick,
+        }
+    }
+}
+
+/// Decides from the environment whether the terminal likely lacks Unicode support
+pub fn detect_ascii() -> bool {
+    let term = std::env::var("TERM").ok();
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|val| !val.is_empty()));
+    is_ascii_terminal(term.as_deref(), locale.as_deref())
+}
+
+/// Returns whether a terminal type or locale rules out Unicode
+/// A missing locale is assumed to support it, as most terminals do
+pub fn is_ascii_terminal(term: Option<&str>, locale: Option<&str>) -> bool {
+    if matches!(term, Some("dumb" | "vt100" | "vt220")) {
+        return true;
+    }
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_lowercase();
+            !locale.contains("utf-8") && !locale.contains("utf8")
+        }
+        None => false,
+    }
+}
+
+/// Represents the file formats a config can be written in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Toml,
+    Json,
+}
+
+/// Reads the config file from the config directory
+/// When both exist, config.toml takes precedence over config.json
+/// Returns None when there is no config file
+pub fn read_config() -> Result<Option<Config>, Error> {
+    let base_dir = match BaseDirs::new() {
+        Some(val) => val,
+        None => return Err(io::Error::other("No home directory found.").into()),
+    };
+    let config_dir = base_dir.config_dir().join("todo");
+
+    for (name, format) in [("config.toml", Format::Toml), ("config.json", Format::Json)] {
+        let file_path = config_dir.join(name);
+        if !file_path.exists() {
+            continue;
+        }
+        let mut file = File::open(file_path)?;
+        let mut as_string = String::new();
+        file.read_to_string(&mut as_string)?;
+
+        return parse_config(&as_string, format).map(Some);
+    }
+    Ok(None)
+}
+
+/// Parses the contents of a config file in the given format
+pub fn parse_config(contents: &str, format: Format) -> Result<Config, Error> {
+    match format {
+        Format::Toml => Ok(toml::from_str(contents)?),
+        Format::Json => Ok(serde_json::from_str(contents)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_and_json_parse_identically() {
+        let toml = r#"
+            delete_selection = "previous"
+            hyperlinks = false
+            priority_tags = ["urgent"]
+
+            [modal]
+            max_width = 100
+
+            [theme]
+            border = "double"
+
+            [[saved_queries]]
+            status = "inprogress"
+        "#;
+        let json = r#"{
+            "delete_selection": "previous",
+            "hyperlinks": false,
+            "priority_tags": ["urgent"],
+            "modal": { "max_width": 100 },
+            "theme": { "border": "double" },
+            "saved_queries": [{ "status": "inprogress" }]
+        }"#;
+        let from_toml = parse_config(toml, Format::Toml).unwrap();
+        let from_json = parse_config(json, Format::Json).unwrap();
+        assert_eq!(format!("{from_toml:?}"), format!("{from_json:?}"));
+        assert_eq!(from_toml.delete_selection, DeleteSelection::Previous);
+        assert_eq!(from_toml.modal.max_width, 100);
+        assert_eq!(from_toml.modal.min_width, 40);
+        assert_eq!(from_toml.theme.border, Border::Double);
+        assert_eq!(
+            from_toml.saved_queries[0].status,
+            Some(QueryStatus::InProgress)
+        );
+    }
+
+    #[test]
+    fn theme_presets() {
+        let palette = |preset| Theme {
+            preset: Some(preset),
+            ..Default::default()
+        };
+        assert_eq!(palette(Preset::Dark).palette().completed, Color::LightGreen);
+        assert_eq!(palette(Preset::Light).palette().border, Color::Black);
+        assert_eq!(
+            palette(Preset::Solarized).palette().selected,
+            Color::Rgb(0x07, 0x36, 0x42)
+        );
+        assert_eq!(Theme::default().palette(), Palette::default());
+
+        // Colors in the config override the preset
+        let toml = r#"
+            [theme]
+            preset = "light"
+            completed_color = "red"
+            in_progress_color = "blue"
+        "#;
+        let config = parse_config(toml, Format::Toml).unwrap();
+        let palette = config.theme.palette();
+        assert_eq!(palette.completed, Color::Red);
+        assert_eq!(palette.in_progress, Color::Blue);
+        assert_eq!(palette.border, Color::Black);
+    }
+}
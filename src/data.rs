@@ -1,14 +1,103 @@
-use crate::app::TodoItem;
+use crate::app::{Priority, Status, TodoItem};
+use crate::config::{Config, JsonFormat};
+use crate::logging;
+use chrono::{Days, NaiveDate};
 use directories::BaseDirs;
-use std::fs::File;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fs::{File, OpenOptions};
 use std::io;
 use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
+/// Name of the scratchpad file, kept next to the todos
+pub const SCRATCH_FILE: &str = "scratch.txt";
+pub const FULL_MESSAGE: &str = "Todo limit reached, archive or clear completed todos first";
+
+/// The version of the todos file's format that is written
+pub const FORMAT_VERSION: u32 = 2;
+/// Name of the list that files without named lists are moved into
+pub const DEFAULT_LIST: &str = "default";
+
+/// The contents of the todos file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TodoFile {
+    /// The id of the item that was selected when the todos were saved
+    #[serde(default)]
+    pub selected: Option<u64>,
+    /// The name of the active list
+    #[serde(default)]
+    pub list: String,
+    pub lists: BTreeMap<String, Vec<TodoItem>>,
+}
+
+impl Default for TodoFile {
+    fn default() -> Self {
+        TodoFile::new(Vec::new())
+    }
+}
+
+impl TodoFile {
+    /// Creates a file with the items in the default list
+    pub fn new(items: Vec<TodoItem>) -> Self {
+        TodoFile {
+            selected: None,
+            list: String::from(DEFAULT_LIST),
+            lists: BTreeMap::from([(String::from(DEFAULT_LIST), items)]),
+        }
+    }
+
+    /// Returns the items of the active list
+    pub fn items(&self) -> &[TodoItem] {
+        self.lists.get(&self.list).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the number of items across all lists
+    pub fn count(&self) -> usize {
+        self.lists.values().map(Vec::len).sum()
+    }
+
+    /// Takes the items of the active list out of the file
+    pub fn take_items(&mut self) -> Vec<TodoItem> {
+        self.lists.remove(&self.list).unwrap_or_default()
+    }
+}
+
+/// The state of a file on disk, used to notice when something else changes it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileStamp {
+    pub modified: Option<SystemTime>,
+    pub len: u64,
+}
+
+#[derive(Debug)]
 pub enum Error {
     IO(std::io::Error),
     Serde(serde_json::Error),
+    Toml(toml::de::Error),
+    /// The directory of a todos file couldn't be created
+    Dir(PathBuf, std::io::Error),
+    /// The todos file was written by a newer version
+    Version(u64),
 }
 
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::IO(e) => write!(f, "File error: {e}"),
+            Error::Serde(e) => write!(f, "Invalid JSON: {e}"),
+            Error::Toml(e) => write!(f, "Invalid config: {e}"),
+            Error::Dir(dir, e) => write!(f, "Can't create directory {}: {e}", dir.display()),
+            Error::Version(v) => write!(f, "Unsupported file version {v}, try updating todui"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 impl From<io::Error> for Error {
     fn from(e: io::Error) -> Self {
         Error::IO(e)
@@ -21,35 +110,701 @@ impl From<serde_json::Error> for Error {
     }
 }
 
-pub fn read_todos() -> Result<Vec<TodoItem>, Error> {
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::Toml(e)
+    }
+}
+
+/// Returns the default location of the todos file
+pub fn default_path() -> Result<PathBuf, Error> {
     let base_dir = match BaseDirs::new() {
         Some(val) => val,
         None => return Err(io::Error::other("No home directory found.").into()),
     };
     let data_dir = base_dir.data_dir();
-    let file_path = data_dir.join("todo").join("todos.json");
+    Ok(data_dir.join("todo").join("todos.json"))
+}
 
-    let mut file = File::open(file_path)?;
-    let mut as_string = String::new();
-    file.read_to_string(&mut as_string)?;
+/// Returns where to keep the todos when their file can't be written, in the temp directory
+pub fn fallback_path(file_path: &Path) -> PathBuf {
+    let file_name = file_path.file_name().unwrap_or("todos.json".as_ref());
+    std::env::temp_dir().join("todo").join(file_name)
+}
 
-    Ok(serde_json::from_str(&as_string)?)
+/// Checks that the todos file's directory can be written to, without touching the file
+pub fn check_writable(file_path: &Path) -> Result<(), Error> {
+    let dir = match file_path.parent() {
+        Some(dir) => dir,
+        None => Path::new("."),
+    };
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(".todo-write-check");
+    File::create(&probe)?;
+    std::fs::remove_file(probe)?;
+    Ok(())
 }
 
-pub fn write_todos(todos: &Vec<TodoItem>) -> Result<(), Error> {
-    let base_dir = match BaseDirs::new() {
-        Some(val) => val,
-        None => return Err(io::Error::other("No home directory found.").into()),
+/// Reads the todos file named by TODUI_FILE
+pub fn file_from_env() -> Option<PathBuf> {
+    match std::env::var_os("TODUI_FILE") {
+        Some(path) if !path.is_empty() => Some(PathBuf::from(path)),
+        _ => None,
+    }
+}
+
+/// Makes sure the directory of a todos file given by the user exists
+pub fn prepare_file(file_path: &Path) -> Result<(), Error> {
+    match file_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => match std::fs::create_dir_all(dir) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(Error::Dir(dir.to_path_buf(), e)),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Returns the given todos file, or else the file of the active profile, or the default location
+pub fn resolve_path(config: &Config, file: Option<&Path>) -> Result<PathBuf, Error> {
+    if let Some(path) = file {
+        return Ok(path.to_path_buf());
+    }
+    let active = config.profile.as_ref();
+    match active.and_then(|name| config.profiles.get(name)) {
+        Some(path) => Ok(path.clone()),
+        None => default_path(),
+    }
+}
+
+/// Returns the names of other profiles that resolve to the active profile's file
+pub fn colliding_profiles(config: &Config) -> Result<Vec<String>, Error> {
+    let active = canonical(&resolve_path(config, None)?);
+    let names = config
+        .profiles
+        .iter()
+        .filter(|(name, _)| Some(*name) != config.profile.as_ref())
+        .filter(|(_, path)| canonical(path) == active)
+        .map(|(name, _)| name.clone())
+        .collect();
+    Ok(names)
+}
+
+/// Canonicalizes a path, even when only its parent directory exists yet
+fn canonical(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+    match (path.parent().map(Path::canonicalize), path.file_name()) {
+        (Some(Ok(parent)), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+pub fn read_todos(file_path: &Path) -> Result<TodoFile, Error> {
+    let read = || -> Result<TodoFile, Error> {
+        let mut file = File::open(file_path)?;
+        let mut as_string = String::new();
+        file.read_to_string(&mut as_string)?;
+
+        migrate(serde_json::from_str(&as_string)?)
     };
-    let data_dir = base_dir.data_dir();
-    let todo_dir = data_dir.join("todo");
-    let file_path = todo_dir.join("todos.json");
+    let result = read();
+    match &result {
+        Ok(todos) => logging::debug(format!(
+            "Read {} todos from {}",
+            todos.count(),
+            file_path.display()
+        )),
+        Err(err) => logging::error(format!("Failed to read {}: {err}", file_path.display())),
+    }
+    result
+}
 
-    let json_string = serde_json::to_string(todos)?;
+/// Brings the contents of a todos file from any older format up to date
+/// New fields are filled with their defaults
+pub fn migrate(value: serde_json::Value) -> Result<TodoFile, Error> {
+    let version = match &value {
+        // Files from before the format was versioned hold only the items
+        serde_json::Value::Array(_) => 0,
+        _ => value.get("version").and_then(|v| v.as_u64()).unwrap_or(1),
+    };
+    let mut value = match version {
+        0 => serde_json::json!({ "version": 1, "items": value }),
+        _ => value,
+    };
+    // Version 1 kept a single list of items
+    if version <= 1 {
+        let items = value["items"].take();
+        value = serde_json::json!({
+            "version": 2,
+            "selected": value["selected"].take(),
+            "list": DEFAULT_LIST,
+            "lists": { DEFAULT_LIST: items },
+        });
+    }
+    if version > FORMAT_VERSION as u64 {
+        return Err(Error::Version(version));
+    }
+    let mut file: TodoFile = serde_json::from_value(value)?;
+    // There is always a list to add to
+    if file.lists.is_empty() {
+        file.lists.insert(String::from(DEFAULT_LIST), Vec::new());
+    }
+    if !file.lists.contains_key(&file.list) {
+        file.list = file.lists.keys().next().cloned().unwrap_or_default();
+    }
+    Ok(file)
+}
 
-    std::fs::create_dir_all(todo_dir)?;
-    let mut file = File::create(file_path)?;
+/// Returns the current state of a file, or None if it can't be read
+pub fn stamp(file_path: &Path) -> Option<FileStamp> {
+    let metadata = std::fs::metadata(file_path).ok()?;
+    Some(FileStamp {
+        modified: metadata.modified().ok(),
+        len: metadata.len(),
+    })
+}
+
+/// Decides whether a file changed since it was last read or written
+/// A file that appears, disappears or has a new size or modification time has changed
+pub fn changed_externally(known: Option<FileStamp>, current: Option<FileStamp>) -> bool {
+    known != current
+}
+
+/// Formats the todos file in the latest version
+fn to_json(file: &TodoFile, format: JsonFormat) -> Result<String, Error> {
+    let mut file = serde_json::to_value(file)?;
+    file["version"] = FORMAT_VERSION.into();
+    let json_string = match format {
+        JsonFormat::Compact => serde_json::to_string(&file)?,
+        JsonFormat::Pretty => serde_json::to_string_pretty(&file)?,
+    };
+    Ok(json_string)
+}
+
+pub fn write_todos(file: &TodoFile, file_path: &Path, format: JsonFormat) -> Result<(), Error> {
+    let write = || -> Result<(), Error> {
+        let json_string = to_json(file, format)?;
+
+        if let Some(todo_dir) = file_path.parent() {
+            std::fs::create_dir_all(todo_dir)?;
+        }
+        write_atomic(file_path, json_string.as_bytes())
+    };
+    let result = write();
+    match &result {
+        Ok(()) => logging::debug(format!(
+            "Wrote {} todos to {}",
+            file.count(),
+            file_path.display()
+        )),
+        Err(err) => logging::error(format!("Failed to write {}: {err}", file_path.display())),
+    }
+    result
+}
+
+/// Returns the temporary file a file is written to before it replaces the file
+fn temp_path(file_path: &Path) -> PathBuf {
+    let mut temp = file_path.as_os_str().to_owned();
+    temp.push(".tmp");
+    PathBuf::from(temp)
+}
+
+/// Writes to a temporary file next to the file, then renames it over the file
+/// A crash part way leaves the file untouched, a stale temporary file is overwritten
+fn write_atomic(file_path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let temp = temp_path(file_path);
+    let mut file = File::create(&temp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    std::fs::rename(&temp, file_path)?;
+    Ok(())
+}
+
+/// Returns a few example todos showing off the features, all tagged #example
+/// The example due date is a week after `today`
+pub fn starter_todos(today: NaiveDate) -> Vec<TodoItem> {
+    let example = |id, todo: &str, info: &str| {
+        TodoItem::builder()
+            .id(id)
+            .todo(todo)
+            .info(info)
+            .tags(["example"])
+    };
+    vec![
+        example(
+            1,
+            "Welcome to todo!",
+            "These are examples, delete them with [d].",
+        )
+        .priority(Priority::High)
+        .build(),
+        example(
+            2,
+            "Edit me with [i]",
+            "The info supports **markdown**, press [Enter] to see it.",
+        )
+        .build(),
+        example(
+            3,
+            "Complete a todo with [c]",
+            "Completed todos are shown in green.",
+        )
+        .status(Status::Completed)
+        .build(),
+        example(
+            4,
+            "Search with [/]",
+            "Press [\\] or [Esc] to show everything again.",
+        )
+        .build(),
+        example(
+            5,
+            "Plan ahead with due dates",
+            "Todos with a due date show it next to their title.",
+        )
+        .due(today + Days::new(7))
+        .subtasks(vec![
+            example(6, "Break big todos into subtasks", "").build(),
+        ])
+        .build(),
+    ]
+}
+
+/// Writes the example todos to a new todos file, an existing file is never touched
+/// Returns whether the examples were written
+pub fn seed_examples(
+    file_path: &Path,
+    format: JsonFormat,
+    today: NaiveDate,
+) -> Result<bool, Error> {
+    if let Some(todo_dir) = file_path.parent() {
+        std::fs::create_dir_all(todo_dir)?;
+    }
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(file_path);
+    let mut file = match file {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::AlreadyExists => return Ok(false),
+        Err(err) => return Err(err.into()),
+    };
+    let json_string = to_json(&TodoFile::new(starter_todos(today)), format)?;
     file.write_all(json_string.as_bytes())?;
+    logging::info(format!("Seeded examples into {}", file_path.display()));
+    Ok(true)
+}
+
+/// Reads the scratchpad, a missing scratchpad is empty
+pub fn read_scratch(file_path: &Path) -> Result<String, Error> {
+    match std::fs::read_to_string(file_path) {
+        Ok(text) => Ok(text),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(err) => Err(err.into()),
+    }
+}
 
+pub fn write_scratch(text: &str, file_path: &Path) -> Result<(), Error> {
+    if let Some(scratch_dir) = file_path.parent() {
+        std::fs::create_dir_all(scratch_dir)?;
+    }
+    std::fs::write(file_path, text)?;
     Ok(())
 }
+
+/// Writes todos as a markdown task list, the info is indented below each todo
+pub fn export_markdown<'a>(todos: impl IntoIterator<Item = &'a TodoItem>) -> String {
+    let mut markdown = String::new();
+    for todo in todos {
+        let checkbox = match todo.status {
+            Status::Todo | Status::InProgress => "[ ]",
+            Status::Completed => "[x]",
+        };
+        markdown.push_str(&format!("- {checkbox} {}\n", todo.todo));
+        for line in todo.info.lines() {
+            markdown.push_str(&format!("  {line}\n"));
+        }
+    }
+    markdown
+}
+
+/// Writes todos as a pretty JSON array, with each todo in the same format as in the todos file
+pub fn export_json<'a>(todos: impl IntoIterator<Item = &'a TodoItem>) -> Result<String, Error> {
+    let todos: Vec<&TodoItem> = todos.into_iter().collect();
+    Ok(serde_json::to_string_pretty(&todos)?)
+}
+
+/// Counts completed todos per day as CSV, oldest day first
+/// Todos without a completion time are ignored
+pub fn export_completion_stats_csv(todos: &[TodoItem]) -> String {
+    let mut per_day: BTreeMap<NaiveDate, usize> = BTreeMap::new();
+    for completed_at in todos.iter().filter_map(|todo| todo.completed_at) {
+        *per_day.entry(completed_at.date_naive()).or_default() += 1;
+    }
+
+    let mut csv = String::from("date,completed\n");
+    for (date, count) in per_day {
+        csv.push_str(&format!("{date},{count}\n"));
+    }
+    csv
+}
+
+/// Returns whether the todos reached the configured maximum
+pub fn is_full(todos: &[TodoItem], config: &Config) -> bool {
+    match config.max_todos {
+        Some(max) => todos.len() >= max,
+        None => false,
+    }
+}
+
+/// Returns the highest id among the todos and their subtasks (at any depth), 0 if there are none
+pub fn max_id(todos: &[TodoItem]) -> u64 {
+    todos
+        .iter()
+        .map(|todo| todo.id.max(max_id(&todo.subtasks)))
+        .max()
+        .unwrap_or(0)
+}
+
+/// Gives a new id to every todo (in any list, subtasks included) without one
+/// or whose id an earlier todo already uses, the active list goes first so its ids are kept
+/// Returns how many duplicate ids were replaced
+pub fn dedup_ids(file: &mut TodoFile) -> usize {
+    let mut next = file
+        .lists
+        .values()
+        .map(|todos| max_id(todos))
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let mut seen = HashSet::new();
+    let list = &file.list;
+    let (active, others): (Vec<_>, Vec<_>) =
+        file.lists.iter_mut().partition(|(name, _)| *name == list);
+    active
+        .into_iter()
+        .chain(others)
+        .map(|(_, todos)| dedup_todos(todos, &mut seen, &mut next))
+        .sum()
+}
+
+/// Gives new ids to the todos (and their subtasks) whose id is missing or already seen
+fn dedup_todos(todos: &mut [TodoItem], seen: &mut HashSet<u64>, next: &mut u64) -> usize {
+    let mut fixed = 0;
+
+    for todo in todos.iter_mut() {
+        // Ids of 0 come from files written before ids existed
+        if todo.id == 0 || !seen.insert(todo.id) {
+            if todo.id != 0 {
+                fixed += 1;
+            }
+            todo.id = *next;
+            seen.insert(*next);
+            *next += 1;
+        }
+        fixed += dedup_todos(&mut todo.subtasks, seen, next);
+    }
+    fixed
+}
+
+/// Writes a value as pretty JSON to a temporary file, lets `edit` modify the file
+/// and parses the result back, leaving the original value untouched on failure
+pub fn edit_as_json<T, F>(value: &T, edit: F) -> Result<T, Error>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce(&Path) -> io::Result<()>,
+{
+    let file_path = std::env::temp_dir().join(format!("todo-{}.json", std::process::id()));
+    std::fs::write(&file_path, serde_json::to_string_pretty(value)?)?;
+
+    let edited = edit(&file_path).and_then(|_| std::fs::read_to_string(&file_path));
+    _ = std::fs::remove_file(&file_path);
+
+    Ok(serde_json::from_str(&edited?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::Priority;
+    use chrono::{Local, TimeZone};
+
+    #[test]
+    fn given_file_overrides_profiles() {
+        let dir = std::env::temp_dir().join("todo_given_file");
+        _ = std::fs::remove_dir_all(&dir);
+        let config = Config {
+            profile: Some(String::from("work")),
+            profiles: BTreeMap::from([(String::from("work"), dir.join("work.json"))]),
+            ..Default::default()
+        };
+        let file = dir.join("lists").join("home.json");
+        assert_eq!(resolve_path(&config, None).unwrap(), dir.join("work.json"));
+        assert_eq!(resolve_path(&config, Some(&file)).unwrap(), file);
+
+        prepare_file(&file).unwrap();
+        assert!(dir.join("lists").is_dir());
+        prepare_file(Path::new("todos.json")).unwrap();
+
+        // A file in the way of the directory
+        std::fs::write(dir.join("blocked"), "").unwrap();
+        let err = prepare_file(&dir.join("blocked").join("todos.json")).unwrap_err();
+        assert!(matches!(err, Error::Dir(ref path, _) if path == &dir.join("blocked")));
+        assert!(err.to_string().starts_with("Can't create directory"));
+    }
+
+    #[test]
+    fn detect_colliding_profiles() {
+        let dir = std::env::temp_dir().join("todo_colliding_profiles");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let config = Config {
+            profile: Some(String::from("work")),
+            profiles: BTreeMap::from([
+                (String::from("work"), dir.join("todos.json")),
+                (String::from("home"), dir.join(".").join("todos.json")),
+                (String::from("misc"), dir.join("misc.json")),
+            ]),
+            ..Default::default()
+        };
+        assert_eq!(colliding_profiles(&config).unwrap(), vec!["home"]);
+    }
+
+    #[test]
+    fn completion_stats_per_day() {
+        let completed = |day, hour| {
+            let mut todo = TodoItem::new(Status::Completed, "done", "");
+            todo.completed_at = Local.with_ymd_and_hms(2024, 3, day, hour, 0, 0).single();
+            todo
+        };
+        let todos = [
+            completed(2, 9),
+            completed(1, 23),
+            TodoItem::new(Status::Todo, "not done", ""),
+            completed(2, 18),
+            TodoItem::new(Status::Completed, "no timestamp", ""),
+        ];
+        assert_eq!(
+            export_completion_stats_csv(&todos),
+            "date,completed\n2024-03-01,1\n2024-03-02,2\n"
+        );
+    }
+
+    #[test]
+    fn dedup_duplicate_ids() {
+        let json = r#"[
+            {"id": 3, "status": "Todo", "todo": "first", "info": ""},
+            {"id": 3, "status": "Todo", "todo": "second", "info": ""},
+            {"status": "Todo", "todo": "legacy", "info": ""}
+        ]"#;
+        let todos: Vec<TodoItem> = serde_json::from_str(json).unwrap();
+        let mut file = TodoFile::new(todos);
+        assert_eq!(dedup_ids(&mut file), 1);
+        let ids: Vec<u64> = file.items().iter().map(|todo| todo.id).collect();
+        assert_eq!(ids, vec![3, 4, 5]);
+        assert_eq!(dedup_ids(&mut file), 0);
+
+        // Ids are unique across lists and subtasks, the active list keeps its ids
+        let mut parent = TodoItem::new(Status::Todo, "parent", "");
+        parent.id = 4;
+        parent.subtasks = vec![file.items()[0].clone()];
+        file.lists.insert(String::from("archive"), vec![parent]);
+        assert_eq!(dedup_ids(&mut file), 2);
+        let ids: Vec<u64> = file.items().iter().map(|todo| todo.id).collect();
+        assert_eq!(ids, vec![3, 4, 5]);
+        let archived = &file.lists["archive"][0];
+        assert_eq!((archived.id, archived.subtasks[0].id), (6, 7));
+    }
+
+    #[test]
+    fn migrate_legacy_files() {
+        let legacy = serde_json::json!([
+            {"status": "Todo", "todo": "old", "info": "from before"},
+            {"status": "Completed", "todo": "done", "info": ""}
+        ]);
+        let file = migrate(legacy).unwrap();
+        assert_eq!(file.selected, None);
+        assert_eq!(file.list, DEFAULT_LIST);
+        let items = file.items();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].todo, "old");
+        assert_eq!(items[0].info, "from before");
+        assert!(matches!(items[1].status, Status::Completed));
+        assert_eq!(items[0].priority, Priority::Medium);
+        assert_eq!(items[0].due, None);
+
+        let single = serde_json::json!({"version": 1, "selected": 4, "items": [
+            {"status": "Todo", "todo": "kept", "info": ""}
+        ]});
+        let file = migrate(single).unwrap();
+        assert_eq!(file.selected, Some(4));
+        assert_eq!(file.items()[0].todo, "kept");
+        let future = serde_json::json!({"version": FORMAT_VERSION + 1, "items": []});
+        assert!(matches!(migrate(future), Err(Error::Version(_))));
+
+        // Old files are written back in the newest format
+        let dir = std::env::temp_dir().join("todo_migrate");
+        let path = dir.join("todos.json");
+        _ = std::fs::create_dir_all(&dir);
+        std::fs::write(&path, r#"[{"status": "Todo", "todo": "old", "info": ""}]"#).unwrap();
+        let file = read_todos(&path).unwrap();
+        write_todos(&file, &path, JsonFormat::Compact).unwrap();
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written["version"], FORMAT_VERSION);
+        assert_eq!(written["lists"][DEFAULT_LIST][0]["todo"], "old");
+    }
+
+    #[test]
+    fn status_compatibility() {
+        // Files from before InProgress existed still load
+        let json = r#"[
+            {"status": "Todo", "todo": "a", "info": ""},
+            {"status": "Completed", "todo": "b", "info": ""}
+        ]"#;
+        let todos: Vec<TodoItem> = serde_json::from_str(json).unwrap();
+        assert_eq!(todos[0].status, Status::Todo);
+        assert_eq!(todos[1].status, Status::Completed);
+
+        let todo = TodoItem::new(Status::InProgress, "c", "");
+        let json = serde_json::to_string(&todo).unwrap();
+        assert!(json.contains(r#""status":"InProgress""#));
+        let todo: TodoItem = serde_json::from_str(&json).unwrap();
+        assert_eq!(todo.status, Status::InProgress);
+    }
+
+    #[test]
+    fn named_lists() {
+        let json = serde_json::json!({"version": 2, "list": "home", "lists": {
+            "home": [{"status": "Todo", "todo": "dishes", "info": ""}],
+            "work": []
+        }});
+        let mut file = migrate(json).unwrap();
+        assert_eq!(file.items()[0].todo, "dishes");
+        assert_eq!(file.count(), 1);
+        assert_eq!(file.take_items().len(), 1);
+        assert!(file.items().is_empty());
+
+        // A missing active list falls back to the first one
+        let json = serde_json::json!({"version": 2, "list": "gone", "lists": {"b": [], "a": []}});
+        assert_eq!(migrate(json).unwrap().list, "a");
+
+        // Without any lists, there is a default one
+        let json = serde_json::json!({"version": 2, "list": "", "lists": {}});
+        let file = migrate(json).unwrap();
+        assert_eq!(file.list, DEFAULT_LIST);
+        assert!(file.lists.contains_key(DEFAULT_LIST));
+    }
+
+    #[test]
+    fn detect_external_changes() {
+        let time = SystemTime::UNIX_EPOCH;
+        let stamp = |secs, len| FileStamp {
+            modified: Some(time + std::time::Duration::from_secs(secs)),
+            len,
+        };
+        assert!(!changed_externally(None, None));
+        assert!(!changed_externally(Some(stamp(10, 5)), Some(stamp(10, 5))));
+        assert!(changed_externally(Some(stamp(10, 5)), Some(stamp(20, 5))));
+        assert!(changed_externally(Some(stamp(10, 5)), Some(stamp(10, 6))));
+        assert!(changed_externally(None, Some(stamp(10, 5))));
+        assert!(changed_externally(Some(stamp(10, 5)), None));
+    }
+
+    #[test]
+    fn compact_and_pretty_round_trip() {
+        let dir = std::env::temp_dir().join("todo_json_format");
+        let compact_path = dir.join("compact.json");
+        let pretty_path = dir.join("pretty.json");
+        let todos = vec![
+            TodoItem::new(Status::Todo, "a", "first"),
+            TodoItem::new(Status::Completed, "b", ""),
+        ];
+        let file = TodoFile::new(todos.clone());
+        write_todos(&file, &compact_path, JsonFormat::Compact).unwrap();
+        write_todos(&file, &pretty_path, JsonFormat::Pretty).unwrap();
+
+        let compact = std::fs::read_to_string(&compact_path).unwrap();
+        let pretty = std::fs::read_to_string(&pretty_path).unwrap();
+        assert!(!compact.contains('\n'));
+        assert!(pretty.contains("\n      {"));
+
+        let from_compact =
+            serde_json::to_value(read_todos(&compact_path).unwrap().items()).unwrap();
+        let from_pretty = serde_json::to_value(read_todos(&pretty_path).unwrap().items()).unwrap();
+        assert_eq!(from_compact, from_pretty);
+        assert_eq!(from_compact, serde_json::to_value(&todos).unwrap());
+    }
+
+    #[test]
+    fn atomic_writes() {
+        let dir = std::env::temp_dir().join("todo_atomic_writes");
+        let path = dir.join("todos.json");
+        let todos = vec![TodoItem::new(Status::Todo, "a", "")];
+        write_todos(&TodoFile::new(todos), &path, JsonFormat::Compact).unwrap();
+
+        // A crash before the rename leaves a partial temporary file behind
+        let temp = temp_path(&path);
+        assert_eq!(temp, dir.join("todos.json.tmp"));
+        std::fs::write(&temp, "[{\"todo\": \"b").unwrap();
+        assert_eq!(read_todos(&path).unwrap().items()[0].todo, "a");
+
+        // The next write replaces it
+        let todos = vec![TodoItem::new(Status::Todo, "c", "")];
+        write_todos(&TodoFile::new(todos), &path, JsonFormat::Compact).unwrap();
+        assert_eq!(read_todos(&path).unwrap().items()[0].todo, "c");
+        assert!(!temp.exists());
+    }
+
+    #[test]
+    fn seed_examples_on_first_run() {
+        let dir = std::env::temp_dir().join("todo_seed_examples");
+        let path = dir.join("todos.json");
+        _ = std::fs::remove_file(&path);
+
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        assert!(seed_examples(&path, JsonFormat::Compact, today).unwrap());
+        let todos = read_todos(&path).unwrap().take_items();
+        assert_eq!(todos.len(), starter_todos(today).len());
+        assert!(todos.iter().all(|todo| todo.tags == ["example"]));
+        assert_eq!(todos[0].priority, Priority::High);
+        assert_eq!(todos[4].due, NaiveDate::from_ymd_opt(2024, 3, 8));
+        assert_eq!(todos[4].subtasks.len(), 1);
+
+        // An existing file is left alone, even an empty list
+        write_todos(&TodoFile::default(), &path, JsonFormat::Compact).unwrap();
+        assert!(!seed_examples(&path, JsonFormat::Compact, today).unwrap());
+        assert!(read_todos(&path).unwrap().items().is_empty());
+    }
+
+    #[test]
+    fn scratch_round_trip() {
+        let dir = std::env::temp_dir().join("todo_scratch");
+        let file_path = dir.join(SCRATCH_FILE);
+        _ = std::fs::remove_file(&file_path);
+        assert_eq!(read_scratch(&file_path).unwrap(), "");
+
+        write_scratch("call the plumber", &file_path).unwrap();
+        assert_eq!(read_scratch(&file_path).unwrap(), "call the plumber");
+    }
+
+    #[test]
+    fn export_subset() {
+        let todos = [
+            TodoItem::new(Status::Todo, "a", "first\nsecond"),
+            TodoItem::new(Status::Todo, "b", ""),
+            TodoItem::new(Status::Completed, "c", ""),
+        ];
+        let subset = [&todos[0], &todos[2]];
+        assert_eq!(
+            export_markdown(subset),
+            "- [ ] a\n  first\n  second\n- [x] c\n"
+        );
+        let json = export_json(subset).unwrap();
+        let exported: Vec<TodoItem> = serde_json::from_str(&json).unwrap();
+        let titles: Vec<&str> = exported.iter().map(|todo| todo.todo.as_str()).collect();
+        assert_eq!(titles, vec!["a", "c"]);
+        assert_eq!(export_markdown(&todos).lines().count(), 5);
+    }
+}
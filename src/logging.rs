@@ -0,0 +1,106 @@
+use std::fmt::Display;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// Name of the log file, kept next to the todos
+pub const LOG_FILE: &str = "todui.log";
+
+/// Represents how detailed the log is, each level includes the ones before it
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Level {
+    Error,
+    Info,
+    Debug,
+}
+
+/// Appends log lines to a file, it never writes to stdout or stderr (the TUI owns them)
+struct Logger {
+    file: Mutex<File>,
+    level: Level,
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Starts logging to a file if TODUI_LOG holds a level (error, info or debug)
+pub fn init_from_env(file_path: &Path) -> io::Result<()> {
+    let level = match std::env::var("TODUI_LOG").ok().as_deref() {
+        Some("error") => Level::Error,
+        Some("info") => Level::Info,
+        Some("debug") => Level::Debug,
+        _ => return Ok(()),
+    };
+    init(file_path, level)
+}
+
+/// Starts logging to a file, only the first call has any effect
+pub fn init(file_path: &Path, level: Level) -> io::Result<()> {
+    if let Some(log_dir) = file_path.parent() {
+        std::fs::create_dir_all(log_dir)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)?;
+    _ = LOGGER.set(Logger {
+        file: Mutex::new(file),
+        level,
+    });
+    Ok(())
+}
+
+/// Writes a timestamped line to the log, if logging is enabled at that level
+pub fn log(level: Level, message: impl Display) {
+    let Some(logger) = LOGGER.get() else {
+        return;
+    };
+    if level > logger.level {
+        return;
+    }
+    let time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+    if let Ok(mut file) = logger.file.lock() {
+        _ = writeln!(file, "{time} {level:?} {message}");
+    }
+}
+
+pub fn error(message: impl Display) {
+    log(Level::Error, message);
+}
+
+pub fn info(message: impl Display) {
+    log(Level::Info, message);
+}
+
+pub fn debug(message: impl Display) {
+    log(Level::Debug, message);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{Status, TodoItem};
+    use crate::data;
+
+    #[test]
+    fn log_load_save_cycle() {
+        let dir = std::env::temp_dir().join("todo_logging");
+        let log_path = dir.join(LOG_FILE);
+        _ = std::fs::remove_file(&log_path);
+        init(&log_path, Level::Debug).unwrap();
+
+        let data_path = dir.join("todos.json");
+        let todos = vec![TodoItem::new(Status::Todo, "logged", "")];
+        let file = data::TodoFile::new(todos);
+        data::write_todos(&file, &data_path, Default::default()).unwrap();
+        data::read_todos(&data_path).unwrap();
+        _ = data::read_todos(&dir.join("missing.json"));
+
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        let wrote = format!("Debug Wrote 1 todos to {}", data_path.display());
+        let read = format!("Debug Read 1 todos from {}", data_path.display());
+        assert!(log.lines().any(|line| line.ends_with(&wrote)));
+        assert!(log.lines().any(|line| line.ends_with(&read)));
+        assert!(log.contains("Error Failed to read"));
+    }
+}
@@ -1,22 +1,61 @@
 use app::App;
+use cli::Command;
 use color_eyre::eyre::Result;
+use ratatui::crossterm::event::{DisableBracketedPaste, EnableBracketedPaste};
+use ratatui::crossterm::execute;
+use std::io;
+use std::process::ExitCode;
 
 mod app;
+mod cli;
+mod clock;
+mod command;
+mod config;
 mod data;
+mod logging;
+mod markdown;
 mod widget;
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     color_eyre::install()?;
 
+    // Run non-interactive commands without the TUI
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let file = match cli::take_file(&mut args)? {
+        Some(path) => Some(path),
+        None => data::file_from_env(),
+    };
+    if let Some(path) = &file {
+        data::prepare_file(path)?;
+    }
+    let examples = match cli::parse(&args)? {
+        Command::Tui { examples } => examples,
+        command => return cli::run(command, file.as_deref()),
+    };
+
     // Setup terminal
     let mut terminal = ratatui::init();
+    execute!(io::stdout(), EnableBracketedPaste)?;
 
     // Run app
     let mut app = App::default();
+    if examples {
+        app.seed_examples();
+    }
+    if let Some(path) = file {
+        app.use_file(path);
+    }
     let result = app.run(&mut terminal);
 
     // Restore terminal
+    _ = execute!(io::stdout(), DisableBracketedPaste);
     ratatui::restore();
 
-    return result;
+    // Leave the list in the normal screen buffer
+    if let Some(summary) = app.exit_summary() {
+        print!("{summary}");
+    }
+
+    result?;
+    Ok(ExitCode::SUCCESS)
 }
@@ -0,0 +1,205 @@
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+
+const HEADER_STYLE: Style = Style::new().bold().underlined();
+const CODE_STYLE: Style = Style::new().fg(Color::Gray).bg(Color::Black);
+const BULLET: &str = "• ";
+const FENCE: &str = "```";
+pub const HIGHLIGHT_STYLE: Style = Style::new().fg(Color::Black).bg(Color::Yellow);
+
+/// Converts lightweight markdown into styled Text
+/// Supports headers (#), bullet lists (- or *), **bold**, *italics* and fenced code blocks
+pub fn to_text(input: &str) -> Text<'static> {
+    let mut in_code = false;
+    let mut lines = Vec::new();
+    for line in input.lines() {
+        // Fences are kept (styled as code) so the block boundaries stay visible
+        let fence = line.trim_start().starts_with(FENCE);
+        if fence || in_code {
+            lines.push(Line::styled(line.to_string(), CODE_STYLE));
+        } else {
+            lines.push(to_line(line));
+        }
+        in_code ^= fence;
+    }
+    Text::from(lines)
+}
+
+/// Converts a single line of markdown into a styled Line
+fn to_line(line: &str) -> Line<'static> {
+    let trimmed = line.trim_start();
+
+    // Headers
+    let level = trimmed.chars().take_while(|&c| c == '#').count();
+    if level > 0 && trimmed[level..].starts_with(' ') {
+        return Line::styled(trimmed[level + 1..].to_string(), HEADER_STYLE);
+    }
+
+    // Bullet lists
+    for marker in ["- ", "* "] {
+        if let Some(rest) = trimmed.strip_prefix(marker) {
+            let indent = " ".repeat(line.len() - trimmed.len());
+            let mut spans = vec![Span::raw(indent + BULLET)];
+            spans.extend(parse_inline(rest));
+            return Line::from(spans);
+        }
+    }
+
+    Line::from(parse_inline(line))
+}
+
+/// Splits text into spans, toggling bold on "**" and italics on "*"
+fn parse_inline(text: &str) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut modifiers = Modifier::empty();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '*' {
+            current.push(c);
+            continue;
+        }
+        // Flush whatever was styled before the marker
+        if !current.is_empty() {
+            let style = Style::new().add_modifier(modifiers);
+            spans.push(Span::styled(std::mem::take(&mut current), style));
+        }
+        if chars.peek() == Some(&'*') {
+            chars.next();
+            modifiers.toggle(Modifier::BOLD);
+        } else {
+            modifiers.toggle(Modifier::ITALIC);
+        }
+    }
+    // Don't forget the leftovers
+    if !current.is_empty() {
+        spans.push(Span::styled(current, Style::new().add_modifier(modifiers)));
+    }
+    spans
+}
+
+/// Styles every occurrence of the query (ignoring case) within the spans of the text
+/// Occurrences split across differently styled spans are not highlighted
+pub fn highlight(text: Text<'static>, query: &str, style: Style) -> Text<'static> {
+    if query.is_empty() {
+        return text;
+    }
+    let lines = text.lines.into_iter().map(|line| Line {
+        spans: line
+            .spans
+            .iter()
+            .flat_map(|span| highlight_span(span, query, style))
+            .collect(),
+        ..line
+    });
+    Text::from(lines.collect::<Vec<_>>())
+}
+
+/// Splits a span around the occurrences of the query, styling the occurrences
+fn highlight_span(span: &Span<'static>, query: &str, style: Style) -> Vec<Span<'static>> {
+    let content = span.content.as_ref();
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for (start, end) in find_ignoring_case(content, query) {
+        if start > last {
+            spans.push(Span::styled(content[last..start].to_string(), span.style));
+        }
+        let matched = content[start..end].to_string();
+        spans.push(Span::styled(matched, span.style.patch(style)));
+        last = end;
+    }
+    if last < content.len() || spans.is_empty() {
+        spans.push(Span::styled(content[last..].to_string(), span.style));
+    }
+    spans
+}
+
+/// Returns the byte ranges of the (non-overlapping) occurrences of the query, ignoring case
+fn find_ignoring_case(text: &str, query: &str) -> Vec<(usize, usize)> {
+    let same = |a: char, b: char| a.to_lowercase().eq(b.to_lowercase());
+    let mut found = Vec::new();
+    let mut starts = text.char_indices();
+    while let Some((start, _)) = starts.next() {
+        let mut rest = text[start..].char_indices();
+        let mut end = start;
+        let matched = query.chars().all(|q| match rest.next() {
+            Some((offset, c)) if same(c, q) => {
+                end = start + offset + c.len_utf8();
+                true
+            }
+            _ => false,
+        });
+        if matched {
+            found.push((start, end));
+            // Continue after the occurrence
+            while starts.offset() < end {
+                starts.next();
+            }
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown_constructs() {
+        let text = to_text("# Title\n- **item** one\nsome *it* here");
+        assert_eq!(text.lines.len(), 3);
+
+        assert_eq!(text.lines[0].style, HEADER_STYLE);
+        assert_eq!(text.lines[0].spans[0].content, "Title");
+
+        let bullet = &text.lines[1].spans;
+        assert_eq!(bullet[0].content, BULLET);
+        assert_eq!(bullet[1].content, "item");
+        assert_eq!(bullet[1].style, Style::new().bold());
+        assert_eq!(bullet[2].content, " one");
+        assert_eq!(bullet[2].style, Style::new());
+
+        let prose = &text.lines[2].spans;
+        assert_eq!(prose[0].content, "some ");
+        assert_eq!(prose[1].content, "it");
+        assert_eq!(prose[1].style, Style::new().italic());
+        assert_eq!(prose[2].content, " here");
+    }
+
+    #[test]
+    fn highlight_every_occurrence() {
+        let text = highlight(
+            to_text("Call Bob, then bob\n**BOB**"),
+            "bob",
+            HIGHLIGHT_STYLE,
+        );
+        let first = &text.lines[0].spans;
+        let contents: Vec<&str> = first.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(contents, ["Call ", "Bob", ", then ", "bob"]);
+        assert_eq!(first[1].style, HIGHLIGHT_STYLE);
+        assert_eq!(first[2].style, Style::new());
+        assert_eq!(first[3].style, HIGHLIGHT_STYLE);
+        // The highlight goes on top of the markdown styling
+        let bold = &text.lines[1].spans[0];
+        assert_eq!(bold.content, "BOB");
+        assert_eq!(bold.style, Style::new().bold().patch(HIGHLIGHT_STYLE));
+
+        assert_eq!(find_ignoring_case("ÄäÄ", "ää"), [(0, 4)]);
+        assert_eq!(find_ignoring_case("aaa", "aa"), [(0, 2)]);
+        assert!(find_ignoring_case("abc", "x").is_empty());
+    }
+
+    #[test]
+    fn code_block() {
+        let text = to_text("run:\n```sh\n# not a header\n**x**\n```\n*after*");
+        assert_eq!(text.lines.len(), 6);
+        assert_eq!(text.lines[0].style, Style::new());
+        for line in &text.lines[1..5] {
+            assert_eq!(line.style, CODE_STYLE);
+        }
+        assert_eq!(text.lines[2].spans[0].content, "# not a header");
+        assert_eq!(text.lines[3].spans[0].content, "**x**");
+        assert_eq!(text.lines[5].spans[0].style, Style::new().italic());
+    }
+}
@@ -1,13 +1,20 @@
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
+use ratatui::style::{Color, Style};
 use ratatui::text::Span;
 use ratatui::widgets::Widget;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 /// Input Field Widget
 #[derive(Default, Debug)]
 pub struct InputField {
     input: String,
     wrapping: Wrap,
+    prompt: String,
+    prompt_style: Style,
+    cursor: usize,
+    line_numbers: bool,
 }
 
 /// Represents different kinds of text wrapping
@@ -22,11 +29,21 @@ pub enum Wrap {
     Word,
 }
 
+/// Represents a cursor motion over the displayed (wrapped) lines
+#[derive(Debug, Clone, Copy)]
+pub enum Motion {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
 impl InputField {
     pub fn new<T: Into<String>>(input: T, wrapping: Wrap) -> Self {
         Self {
             input: input.into(),
             wrapping,
+            ..Default::default()
         }
     }
 
@@ -38,30 +55,123 @@ impl InputField {
         self.wrapping = wrapping;
     }
 
+    /// Sets a prompt shown before the input, the input is indented to its width
+    pub fn set_prompt<T: Into<String>>(&mut self, prompt: T, style: Style) {
+        self.prompt = prompt.into();
+        self.prompt_style = style;
+    }
+
+    /// Sets the index of the cursor, which is kept visible when lines don't wrap
+    pub fn set_cursor(&mut self, index: usize) {
+        self.cursor = index;
+    }
+
+    /// Sets whether the displayed lines are numbered in a gutter before the input
+    pub fn set_line_numbers(&mut self, line_numbers: bool) {
+        self.line_numbers = line_numbers;
+    }
+
+    /// Returns the part of the area the input occupies (right of the prompt and gutter)
+    fn input_area(&self, area: Rect) -> Rect {
+        let area = self.gutter_area(area);
+        let gutter_width = match self.line_numbers {
+            true => self.gutter_width(area),
+            false => 0,
+        };
+        Rect {
+            x: area.x + gutter_width,
+            width: area.width - gutter_width,
+            ..area
+        }
+    }
+
+    /// Returns the part of the area right of the prompt, where the gutter starts
+    fn gutter_area(&self, area: Rect) -> Rect {
+        let prompt_width = u16::min(self.prompt.chars().count() as u16, area.width);
+        Rect {
+            x: area.x + prompt_width,
+            width: area.width - prompt_width,
+            ..area
+        }
+    }
+
+    /// Returns the width of the gutter, wide enough for the number of the last line and a space
+    /// A wider gutter can wrap the input onto more lines, so it grows until they fit
+    fn gutter_width(&self, area: Rect) -> u16 {
+        let mut width = 2;
+        loop {
+            let text_area = Rect {
+                width: area.width.saturating_sub(width),
+                ..area
+            };
+            let needed = self.lines(text_area).len().max(1).to_string().len() as u16 + 1;
+            if needed <= width || width >= area.width {
+                return u16::min(width, area.width);
+            }
+            width = needed;
+        }
+    }
+
     pub fn get_cursor_at(&self, area: Rect, index: usize) -> (u16, u16) {
-        if self.input.len() == 0 {
+        let area = self.input_area(area);
+        if self.input.is_empty() {
             return (area.x, area.y);
         }
 
-        let mut index = usize::min(index, self.input.len() - 1);
-        let mut y = 0;
-        let lines = self.lines(area);
+        let index = usize::min(index, self.input.chars().count());
+        let (column, y) = self.cursor_column(&self.lines(area), area, index);
+        let offset = horizontal_offset(column, area.width);
+        (area.x + (column - offset) as u16, area.y + y as u16)
+    }
 
-        for line in lines {
-            if index >= line.len() {
-                index -= line.len();
-                y += 1;
-                continue;
-            }
-            return (area.x + index as u16, area.y + y as u16);
+    /// Returns the (column, line) the cursor is drawn at for an index within the displayed lines
+    /// Past the last character, it trails the last line, or starts the next if the line is full
+    fn cursor_column(&self, lines: &[String], area: Rect, index: usize) -> (usize, usize) {
+        if let Some(position) = locate(&self.input, lines, index) {
+            return position;
+        }
+        let (width, height) = (area.width as usize, area.height as usize);
+        let y = lines.len().saturating_sub(1);
+        let column = lines.last().map_or(0, |line| line.chars().count());
+        match self.wrapping {
+            Wrap::None => (column, y),
+            _ if column < width => (column, y),
+            _ if y + 1 < height => (0, y + 1),
+            // No room for another line, so stay in the last cell
+            _ => (width.saturating_sub(1), y),
+        }
+    }
+
+    /// Translates a motion over the displayed lines into a new (linear) cursor index
+    /// Moving past the end of a line continues on the next, the end of the input is kept
+    pub fn move_cursor(&self, area: Rect, index: usize, motion: Motion) -> usize {
+        let lines = self.lines(self.input_area(area));
+        let len = self.input.chars().count();
+        let index = usize::min(index, len);
+        let (column, y) = locate(&self.input, &lines, index).unwrap_or_else(|| {
+            let last = lines.len().saturating_sub(1);
+            (lines.get(last).map_or(0, |line| line.chars().count()), last)
+        });
+        match motion {
+            Motion::Left => index.saturating_sub(1),
+            Motion::Right => usize::min(index + 1, len),
+            Motion::Up if y > 0 => index_at(&self.input, &lines, column, y - 1),
+            Motion::Down if y + 1 < lines.len() => index_at(&self.input, &lines, column, y + 1),
+            Motion::Up | Motion::Down => index,
         }
-        (area.x + area.width - 1, area.y + area.height - 1)
+    }
+
+    /// Returns how many columns the lines are scrolled to keep the cursor visible
+    fn scroll(&self, lines: &[String], area: Rect) -> usize {
+        let index = usize::min(self.cursor, self.input.chars().count());
+        let (column, _) = self.cursor_column(lines, area, index);
+        horizontal_offset(column, area.width)
     }
 
     pub fn lines(&self, area: Rect) -> Vec<String> {
         match self.wrapping {
             Wrap::None => self.input.lines().map(str::to_string).collect(),
-            Wrap::Character => todo!(),
+            Wrap::Character => wrap_chars(&self.input, (area.width, area.height)),
             Wrap::Word => wrap_words(&self.input, (area.width, area.height)),
         }
     }
@@ -69,14 +179,241 @@ impl InputField {
 
 impl Widget for &InputField {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let mut area = area;
-        for line in self.lines(area) {
+        Span::styled(self.prompt.as_str(), self.prompt_style).render(area, buf);
+        let mut gutter = self.gutter_area(area);
+        let mut area = self.input_area(area);
+        let lines = self.lines(area);
+        if self.line_numbers {
+            gutter.width = area.x - gutter.x;
+            let number_width = gutter.width.saturating_sub(1) as usize;
+            for number in 1..=lines.len().min(gutter.height as usize) {
+                let number = format!("{number:>number_width$}");
+                Span::styled(number, Style::new().fg(Color::DarkGray)).render(gutter, buf);
+                gutter.y += 1;
+            }
+        }
+        let offset = self.scroll(&lines, area);
+        for line in lines {
+            let line: String = line.chars().skip(offset).collect();
             Span::raw(line).render(area, buf);
             area.y += 1;
         }
     }
 }
 
+/// Returns the character index where each displayed line starts in the input
+/// The newlines ending lines aren't displayed, so they are skipped over
+fn line_starts(input: &str, lines: &[String]) -> Vec<usize> {
+    let rest = |offset: usize| input.get(offset..).unwrap_or("");
+    let mut starts = Vec::with_capacity(lines.len());
+    let mut offset = 0;
+    for (y, line) in lines.iter().enumerate() {
+        if y > 0 && rest(offset).starts_with('\n') {
+            offset += 1;
+        }
+        // Word wrapping leaves out blank lines
+        while !rest(offset).starts_with(line.as_str()) && rest(offset).starts_with('\n') {
+            offset += 1;
+        }
+        starts.push(
+            input
+                .get(..offset)
+                .map_or(0, |before| before.chars().count()),
+        );
+        offset += line.len();
+    }
+    starts
+}
+
+/// Returns the (column, line) of a character index within lines, or None if it is past them
+/// An index on a newline is at the end of the line it ends
+fn locate(input: &str, lines: &[String], index: usize) -> Option<(usize, usize)> {
+    let starts = line_starts(input, lines);
+    for (y, line) in lines.iter().enumerate() {
+        let end = starts[y] + line.chars().count();
+        let ends_with_newline = starts.get(y + 1).is_some_and(|&next| next > end);
+        if index < end || (index == end && ends_with_newline) {
+            return Some((index.saturating_sub(starts[y]), y));
+        }
+    }
+    None
+}
+
+/// Returns the (linear) index of a column on a line, clamped to the line
+/// Only lines ending the input or a paragraph let the column go past their last character
+fn index_at(input: &str, lines: &[String], column: usize, y: usize) -> usize {
+    let starts = line_starts(input, lines);
+    let len = lines[y].chars().count();
+    let end = starts[y] + len;
+    let max_column = match starts.get(y + 1).is_none_or(|&next| next > end) {
+        true => len,
+        false => len.saturating_sub(1),
+    };
+    starts[y] + usize::min(column, max_column)
+}
+
+/// Returns the start of the first word after the index, or the end of the text
+/// Words are split like word wrapping splits them, so only ASCII is supported
+pub fn next_word(text: &str, index: usize) -> usize {
+    word_starts(text)
+        .find(|&start| start > index)
+        .unwrap_or(text.len())
+}
+
+/// Returns the start of the last word before the index, or the start of the text
+pub fn previous_word(text: &str, index: usize) -> usize {
+    word_starts(text)
+        .take_while(|&start| start < index)
+        .last()
+        .unwrap_or(0)
+}
+
+/// Returns where the words (not the whitespace between them) of ASCII text start
+fn word_starts(text: &str) -> impl Iterator<Item = usize> {
+    tokenize_ascii(text)
+        .into_iter()
+        .filter(|&(start, _)| !text.as_bytes()[start].is_ascii_whitespace())
+        .map(|(start, _)| start)
+}
+
+/// Returns how many columns to scroll for a column to fit within the width
+/// The view only scrolls once the column passes the right edge
+fn horizontal_offset(column: usize, width: u16) -> usize {
+    column.saturating_sub(width.saturating_sub(1) as usize)
+}
+
+/// Cuts text down to a width (in columns), ending it with an ellipsis when it was cut
+/// Never splits a grapheme, so multibyte text is safe to truncate
+pub fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    let mut used = 0;
+    for grapheme in text.graphemes(true) {
+        let grapheme_width = grapheme.width();
+        // Leave a column for the ellipsis
+        if used + grapheme_width + 1 > width {
+            break;
+        }
+        used += grapheme_width;
+        truncated.push_str(grapheme);
+    }
+    if width > 0 {
+        truncated.push('…');
+    }
+    truncated
+}
+
+/// Removes control characters (ANSI escape sequences included), keeping newlines and tabs
+/// Carriage returns become newlines, so pasted Windows line endings survive
+pub fn sanitize(text: &str) -> String {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut clean = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' | '\t' => clean.push(c),
+            // CSI sequences end on a letter (or similar), like "\x1B[31m"
+            '\x1B' if chars.next_if_eq(&'[').is_some() => {
+                while chars.next_if(|c| !('@'..='~').contains(c)).is_some() {}
+                chars.next();
+            }
+            // OSC sequences end on a bell or "\x1B\\", like hyperlinks
+            '\x1B' if chars.next_if_eq(&']').is_some() => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1B' && chars.next_if_eq(&'\\').is_some()) {
+                        break;
+                    }
+                }
+            }
+            // Any other escape covers the character after it
+            '\x1B' => {
+                chars.next();
+            }
+            c if c.is_control() => {}
+            c => clean.push(c),
+        }
+    }
+    clean
+}
+
+/// Wraps a label in an OSC 8 escape sequence so terminals render it as a link
+pub fn hyperlink(url: &str, label: &str) -> String {
+    format!("\x1B]8;;{url}\x07{label}\x1B]8;;\x07")
+}
+
+/// Turns every URL already rendered within an area of the Buffer into a hyperlink
+pub fn linkify(area: Rect, buf: &mut Buffer) {
+    for y in area.top()..area.bottom() {
+        let row: String = (area.left()..area.right())
+            .map(|x| match buf[(x, y)].symbol().chars().next() {
+                Some(c) if c.is_ascii() => c,
+                _ => ' ',
+            })
+            .collect();
+        for (start, end) in find_urls(&row) {
+            let url = &row[start..end];
+            // Terminals miscount the width of escape sequences, so the link is
+            // emitted in two character chunks (the width ratatui expects)
+            for chunk_start in (start..end).step_by(2) {
+                let chunk_end = usize::min(chunk_start + 2, end);
+                let x = area.x + chunk_start as u16;
+                buf[(x, y)].set_symbol(&hyperlink(url, &row[chunk_start..chunk_end]));
+            }
+        }
+    }
+}
+
+/// Returns indexes to the URLs (http or https) within a line
+/// ONLY WORKS FOR ASCII STRINGS
+fn find_urls(line: &str) -> Vec<(usize, usize)> {
+    let mut urls = Vec::new();
+    let mut pos = 0;
+    while let Some(offset) = line[pos..].find("http") {
+        let start = pos + offset;
+        let rest = &line[start..];
+        let url = &rest[..rest.find(char::is_whitespace).unwrap_or(rest.len())];
+        // Punctuation directly after a URL is most likely part of the prose
+        let end = start
+            + url
+                .trim_end_matches([',', '.', ';', ':', '!', '?', ')'])
+                .len();
+        if rest.starts_with("http://") || rest.starts_with("https://") {
+            urls.push((start, end));
+        }
+        pos = usize::max(end, start + 1);
+    }
+    urls
+}
+
+/// Converts a &str to a Vec<String> where each String is a line
+/// Lines are broken exactly at the width, regardless of words
+fn wrap_chars(string: &str, size: (u16, u16)) -> Vec<String> {
+    let width = size.0 as usize;
+    let height = size.1 as usize;
+    let mut result = Vec::new();
+    if width == 0 {
+        return result;
+    }
+
+    for raw_line in string.lines() {
+        let chars: Vec<char> = raw_line.chars().collect();
+        let mut chunks: Vec<String> = chars.chunks(width).map(String::from_iter).collect();
+        // An empty line still takes up a line
+        if chunks.is_empty() {
+            chunks.push(String::new());
+        }
+        for chunk in chunks {
+            if result.len() >= height {
+                return result;
+            }
+            result.push(chunk);
+        }
+    }
+    result
+}
+
 /// Converts a &str to a Vec<String> where each String is a line
 /// Enforces word wrapping
 /// ONLY WORKS FOR ASCII STRINGS
@@ -211,8 +548,190 @@ mod tests {
         assert_eq!(input.get_cursor_at(area, 0), (1, 1));
         assert_eq!(input.get_cursor_at(area, 6), (5, 2));
         assert_eq!(input.get_cursor_at(area, 12), (1, 4));
-        assert_eq!(input.get_cursor_at(area, usize::MAX), (1, 4));
+        // Typing at the end puts the cursor after the last character
+        assert_eq!(input.get_cursor_at(area, 13), (2, 4));
+        assert_eq!(input.get_cursor_at(area, usize::MAX), (2, 4));
+        // A full last line continues on the next
+        let input = InputField::new(String::from("A wraps"), Wrap::Word);
+        assert_eq!(input.get_cursor_at(area, 7), (1, 3));
+        let input = InputField::new(String::from("abcde"), Wrap::Character);
+        assert_eq!(input.get_cursor_at(area, 5), (1, 2));
+        let input = InputField::new(String::from("abcdef"), Wrap::None);
+        assert_eq!(input.get_cursor_at(area, 6), (5, 1));
         let input = InputField::new(String::from(""), Wrap::Word);
         assert_eq!(input.get_cursor_at(area, 1), (1, 1));
     }
+
+    #[test]
+    fn wrap_characters() {
+        let area = Rect {
+            x: 1,
+            y: 1,
+            width: 5,
+            height: 5,
+        };
+        let input = InputField::new(String::from("abcdefghij"), Wrap::Character);
+        assert_eq!(input.lines(area), ["abcde", "fghij"]);
+        let input = InputField::new(String::from("A wrap occurs"), Wrap::Character);
+        assert_eq!(input.lines(area), ["A wra", "p occ", "urs"]);
+        assert_eq!(input.get_cursor_at(area, 6), (2, 2));
+        let input = InputField::new(String::from("abcdefg\n\nhi"), Wrap::Character);
+        assert_eq!(input.lines(area), ["abcde", "fg", "", "hi"]);
+
+        // Lines past the height are cut off
+        let area = Rect { height: 2, ..area };
+        let input = InputField::new(String::from("abcdefghijklmnop"), Wrap::Character);
+        assert_eq!(input.lines(area), ["abcde", "fghij"]);
+        let input = InputField::new(String::from("ab\ncd\nef"), Wrap::Character);
+        assert_eq!(input.lines(area), ["ab", "cd"]);
+    }
+
+    #[test]
+    fn cursor_after_newlines() {
+        let area = Rect::new(0, 0, 5, 5);
+        let input = InputField::new("ab\n\ncd", Wrap::None);
+        assert_eq!(input.get_cursor_at(area, 2), (2, 0));
+        assert_eq!(input.get_cursor_at(area, 3), (0, 1));
+        assert_eq!(input.get_cursor_at(area, 4), (0, 2));
+        assert_eq!(input.get_cursor_at(area, 6), (2, 2));
+        assert_eq!(input.move_cursor(area, 1, Motion::Down), 3);
+        assert_eq!(input.move_cursor(area, 5, Motion::Up), 3);
+
+        // Word wrapping leaves out the blank line
+        let input = InputField::new("A wrap\n\nnext", Wrap::Word);
+        assert_eq!(input.lines(area), ["A ", "wrap", "next"]);
+        assert_eq!(input.get_cursor_at(area, 6), (4, 1));
+        assert_eq!(input.get_cursor_at(area, 9), (1, 2));
+        assert_eq!(input.move_cursor(area, 9, Motion::Up), 3);
+    }
+
+    #[test]
+    fn word_jumps() {
+        let text = "one  two\nthree";
+        assert_eq!(next_word(text, 0), 5);
+        assert_eq!(next_word(text, 5), 9);
+        assert_eq!(next_word(text, 9), text.len());
+        assert_eq!(previous_word(text, text.len()), 9);
+        assert_eq!(previous_word(text, 9), 5);
+        assert_eq!(previous_word(text, 6), 5);
+        assert_eq!(previous_word(text, 0), 0);
+        assert_eq!(next_word("", 0), 0);
+    }
+
+    #[test]
+    fn get_cursor_pos_with_prompt() {
+        let area = Rect {
+            x: 1,
+            y: 1,
+            width: 11,
+            height: 5,
+        };
+        let mut input = InputField::new(String::from("A wrap occurs"), Wrap::Word);
+        input.set_prompt("Todo: ", Style::new());
+        assert_eq!(input.get_cursor_at(area, 0), (7, 1));
+        assert_eq!(input.get_cursor_at(area, 6), (11, 2));
+        assert_eq!(input.get_cursor_at(area, 12), (7, 4));
+        let mut input = InputField::new(String::from(""), Wrap::Word);
+        input.set_prompt("Todo: ", Style::new());
+        assert_eq!(input.get_cursor_at(area, 1), (7, 1));
+    }
+
+    #[test]
+    fn horizontal_scroll() {
+        assert_eq!(horizontal_offset(0, 10), 0);
+        assert_eq!(horizontal_offset(9, 10), 0);
+        assert_eq!(horizontal_offset(10, 10), 1);
+        assert_eq!(horizontal_offset(25, 10), 16);
+
+        let area = Rect::new(0, 0, 5, 1);
+        let mut input = InputField::new("abcdefgh", Wrap::None);
+        assert_eq!(input.get_cursor_at(area, 2), (2, 0));
+        assert_eq!(input.get_cursor_at(area, 7), (4, 0));
+
+        input.set_cursor(7);
+        let mut buf = Buffer::empty(area);
+        input.render(area, &mut buf);
+        assert_eq!(buf, Buffer::with_lines(["defgh"]));
+    }
+
+    #[test]
+    fn sanitize_control_characters() {
+        let pasted = "\x1B[1;31mred\x1B[0m\tcell\r\nnext\x0Cpage\x07\x00";
+        assert_eq!(sanitize(pasted), "red\tcell\nnextpage");
+        assert_eq!(sanitize(&hyperlink("https://a.b", "link")), "link");
+        assert_eq!(sanitize("a\rb\x1B7c"), "a\nbc");
+        assert_eq!(sanitize("plain ünïcode\n"), "plain ünïcode\n");
+    }
+
+    #[test]
+    fn truncate_with_ellipsis() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("exactly", 7), "exactly");
+        assert_eq!(truncate("truncated text", 6), "trunc…");
+        assert_eq!(truncate("anything", 1), "…");
+        assert_eq!(truncate("anything", 0), "");
+        // Accents (combining marks) stay on their letter
+        assert_eq!(truncate("cafe\u{301} au lait", 5), "cafe\u{301}…");
+        // Wide characters take two columns
+        assert_eq!(truncate("日本語のメモ", 7), "日本語…");
+        assert_eq!(truncate("日本語のメモ", 6), "日本…");
+    }
+
+    #[test]
+    fn line_number_gutter() {
+        let area = Rect::new(0, 0, 8, 12);
+        let mut input = InputField::new("a b c d e f g h i j k", Wrap::Word);
+        assert_eq!(input.get_cursor_at(area, 2), (2, 0));
+
+        // A gutter for one digit and a space
+        input.set_line_numbers(true);
+        let short = Rect::new(0, 0, 8, 3);
+        assert_eq!(input.get_cursor_at(short, 2), (4, 0));
+        assert_eq!(input.get_cursor_at(short, 6), (2, 1));
+
+        // Ten or more lines need a wider gutter
+        input.set_input("a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk");
+        assert_eq!(input.get_cursor_at(area, 0), (3, 0));
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 2));
+        input.render(Rect::new(0, 0, 8, 2), &mut buf);
+        let mut expected = Buffer::with_lines([" 1 a    ", " 2 b    "]);
+        expected.set_style(Rect::new(0, 0, 2, 2), Style::new().fg(Color::DarkGray));
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn visual_cursor_motions() {
+        // Displayed as "A ", "wrap ", "occur", "s"
+        let area = Rect::new(0, 0, 5, 5);
+        let input = InputField::new("A wrap occurs", Wrap::Word);
+        // Right at the end of a line goes to the start of the next
+        assert_eq!(input.move_cursor(area, 1, Motion::Right), 2);
+        assert_eq!(input.get_cursor_at(area, 2), (0, 1));
+        assert_eq!(input.move_cursor(area, 2, Motion::Left), 1);
+        // The very end stays put
+        assert_eq!(input.move_cursor(area, 13, Motion::Right), 13);
+        // Up and down keep the column, clamped to the line
+        assert_eq!(input.move_cursor(area, 6, Motion::Down), 11);
+        assert_eq!(input.move_cursor(area, 10, Motion::Up), 5);
+        assert_eq!(input.move_cursor(area, 11, Motion::Down), 13);
+        assert_eq!(input.move_cursor(area, 5, Motion::Up), 1);
+        assert_eq!(input.move_cursor(area, 1, Motion::Up), 1);
+
+        // Indices count characters, like the cursor of the input
+        let input = InputField::new("åäö\nxyz", Wrap::Character);
+        assert_eq!(input.move_cursor(area, 2, Motion::Down), 6);
+        assert_eq!(input.move_cursor(area, 6, Motion::Up), 2);
+        assert_eq!(input.move_cursor(area, 6, Motion::Right), 7);
+        assert_eq!(input.get_cursor_at(area, 7), (3, 1));
+    }
+
+    #[test]
+    fn hyperlink_escape_sequence() {
+        assert_eq!(
+            hyperlink("https://example.com", "example"),
+            "\x1B]8;;https://example.com\x07example\x1B]8;;\x07"
+        );
+        let line = "see https://a.io and http://b.io/x, not httpx";
+        assert_eq!(find_urls(line), vec![(4, 16), (21, 34)]);
+    }
 }